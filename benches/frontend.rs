@@ -0,0 +1,97 @@
+//! Lexer and parser throughput on a generated multi-megabyte script.
+//!
+//! Run with `cargo bench --bench frontend`. Pass a size in megabytes to
+//! change the input, e.g. `cargo bench --bench frontend -- 16`.
+
+use std::{
+    fmt::Write,
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use codecrafters_interpreter::{lexer::Lexer, parser::Parser};
+
+const DEFAULT_MEGABYTES: usize = 4;
+const ROUNDS: usize = 10;
+
+/// A script mixing the constructs real programs use, with many distinct
+/// identifiers so shared names see both repeats and new ones.
+fn script(megabytes: usize) -> String {
+    let mut source = String::new();
+    let mut i = 0;
+    while source.len() < megabytes * 1024 * 1024 {
+        let _ = write!(
+            source,
+            r#"
+// Block {i}: a class, a function and some loops.
+class Shape{i} < Base {{
+    init(width, height) {{
+        this.width = width;
+        this.height = height;
+    }}
+    area() {{ return this.width * this.height; }}
+}}
+
+fun compute{i}(n, label) {{
+    var total = 0;
+    for (var k = 0; k < n; k = k + 1) {{
+        if (k / 2 == 0 and k != 3) {{ total = total + k * 1.5e2; }}
+        else {{ total = total - 0x1F; }}
+    }}
+    while (total > 1_000) total = total / 2;
+    print label + "\tdone: " + str(total);
+    return Shape{i}(total, 2).area();
+}}
+var result{i} = compute{i}({i}, "block {i}");
+"#
+        );
+        i += 1;
+    }
+    source
+}
+
+/// Runs `f` several times and returns the fastest round.
+fn best_of(mut f: impl FnMut()) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn report(name: &str, bytes: usize, elapsed: Duration) {
+    let megabytes = bytes as f64 / (1024.0 * 1024.0);
+    println!(
+        "{name:<8} {:>9.2} ms {:>9.1} MB/s",
+        elapsed.as_secs_f64() * 1000.0,
+        megabytes / elapsed.as_secs_f64()
+    );
+}
+
+fn main() {
+    let megabytes = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_MEGABYTES);
+    let source = script(megabytes);
+    println!(
+        "input    {:.1} MB, best of {ROUNDS} rounds",
+        source.len() as f64 / (1024.0 * 1024.0)
+    );
+
+    let lex = best_of(|| {
+        let tokens = Lexer::from_source(&source).count();
+        black_box(tokens);
+    });
+    report("lex", source.len(), lex);
+
+    let parse = best_of(|| {
+        let stmts = Parser::new(Lexer::from_source(&source)).parse();
+        assert!(stmts.is_ok(), "the generated script should parse");
+        black_box(stmts.ok());
+    });
+    report("parse", source.len(), parse);
+}
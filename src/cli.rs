@@ -1,15 +1,49 @@
 use clap::{Parser, Subcommand};
 
+use codecrafters_interpreter::{
+    diagnostics::ErrorFormat,
+    lexer::{LexerResult, Source},
+};
+
 #[derive(Debug, Parser)]
 pub struct Args {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// How errors are reported; defaults to `human` when stdout is a terminal
+    /// and `plain` otherwise.
+    #[arg(long, global = true, value_enum)]
+    pub error_format: Option<ErrorFormat>,
 }
 
 #[derive(Debug, Subcommand)]
 pub enum Commands {
-    Tokenize { filename: String },
-    Parse { filename: String },
-    Evaluate { filename: String },
-    Run { filename: String },
+    Tokenize(Input),
+    Parse(Input),
+    Evaluate(Input),
+    Run(Input),
+    /// Starts an interactive prompt; globals persist between entries.
+    Repl,
+}
+
+/// Where a subcommand reads its Lox source from.
+#[derive(Debug, clap::Args)]
+#[group(required = true, multiple = false)]
+pub struct Input {
+    /// Source file, or `-` to read standard input.
+    pub filename: Option<String>,
+
+    /// Lox code to use instead of a file.
+    #[arg(short, long, value_name = "CODE", allow_hyphen_values = true)]
+    pub eval: Option<String>,
+}
+
+impl Input {
+    pub async fn source(&self) -> LexerResult<Source> {
+        match (&self.eval, &self.filename) {
+            (Some(code), _) => Ok(Source::new("<eval>", code.as_str())),
+            (None, Some(filename)) => Source::read(filename).await,
+            (None, None) => unreachable!("clap requires one of the inputs"),
+        }
+    }
 }
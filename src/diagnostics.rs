@@ -0,0 +1,368 @@
+//! Rendering of lexer, parser and runtime errors against the source they came from.
+
+use std::fmt::Write as _;
+
+use crate::{
+    interpreter::{EvaluationError, TraceFrame},
+    lexer::{LexerError, Span},
+    parser::error::ParserError,
+    resolver::ResolverError,
+};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// Frames of a runtime error trace shown before the rest are elided.
+const MAX_TRACE_FRAMES: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ErrorFormat {
+    /// `file:line:col` header, the offending line and a caret under the span.
+    Human,
+    /// One report per error in the reference interpreter's format.
+    Plain,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Option<Span>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+    /// Report in the reference interpreter's format, used by [`ErrorFormat::Plain`].
+    pub summary: String,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>, span: Option<Span>, summary: impl ToString) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            notes: vec![],
+            help: None,
+            summary: summary.to_string(),
+        }
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+}
+
+pub trait ToDiagnostic {
+    fn to_diagnostic(&self) -> Diagnostic;
+}
+
+impl ToDiagnostic for LexerError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            LexerError::SourceError(err) => {
+                Diagnostic::new(format!("could not read source: {err}"), None, self)
+            }
+            LexerError::UnexpectedCharacter { span, ch } => {
+                Diagnostic::new(format!("Unexpected character: {ch}"), Some(*span), self)
+            }
+            LexerError::UnterminatedString(span) => {
+                Diagnostic::new("Unterminated string.", Some(*span), self)
+                    .with_help("add a closing `\"` to end the string")
+            }
+            LexerError::InvalidEscape { span, sequence } => Diagnostic::new(
+                format!("Invalid escape sequence: {sequence}"),
+                Some(*span),
+                self,
+            )
+            .with_help(r#"valid escapes are \n, \t, \r, \0, \", \\ and \u{...}"#),
+            LexerError::MalformedNumber { span, lexeme } => {
+                Diagnostic::new(format!("Malformed number: {lexeme}"), Some(*span), self).with_help(
+                    "`_` may only separate two digits, and `0x` and `0b` need digits of their base",
+                )
+            }
+            LexerError::UnterminatedBlockComment(span) => {
+                Diagnostic::new("Unterminated block comment.", Some(*span), self)
+                    .with_help("add a closing `*/`; nested comments need one each")
+            }
+        }
+    }
+}
+
+impl ToDiagnostic for ParserError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParserError::LexerError(err) => err.to_diagnostic(),
+            ParserError::ExpectedExpression { span, .. } => {
+                Diagnostic::new("Expect expression.", Some(*span), self)
+            }
+            ParserError::UnmatchedParens(span) => {
+                Diagnostic::new("Unmatched parens", Some(*span), self)
+                    .with_note("this `(` is never closed")
+                    .with_help("add a closing `)`")
+            }
+            ParserError::UnexpectedToken(span) => {
+                Diagnostic::new("Unexpected token", Some(*span), self)
+            }
+            ParserError::Expected { span, message, .. } => {
+                Diagnostic::new(*message, Some(*span), self)
+            }
+            ParserError::TooDeeplyNested { span, .. } => {
+                Diagnostic::new("Too much nesting.", Some(*span), self)
+                    .with_help("split the expression or block into smaller pieces")
+            }
+            ParserError::InvalidAssignmentTarget(span) => {
+                Diagnostic::new("Invalid assignment target.", Some(*span), self)
+                    .with_help("only variables can be assigned to")
+            }
+        }
+    }
+}
+
+impl ToDiagnostic for ResolverError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ResolverError::ReadInOwnInitializer { span, name } => Diagnostic::new(
+                "Can't read local variable in its own initializer.",
+                Some(*span),
+                self,
+            )
+            .with_help(format!(
+                "`{name}` is not defined until its initializer finishes"
+            )),
+            ResolverError::AlreadyDeclared { span, name } => Diagnostic::new(
+                "Already a variable with this name in this scope.",
+                Some(*span),
+                self,
+            )
+            .with_help(format!("assign to `{name}` instead of declaring it again")),
+            ResolverError::TopLevelReturn(span) => {
+                Diagnostic::new("Can't return from top-level code.", Some(*span), self)
+            }
+            ResolverError::ReturnFromInitializer(span) => Diagnostic::new(
+                "Can't return a value from an initializer.",
+                Some(*span),
+                self,
+            )
+            .with_note("`init` always returns the instance being initialized"),
+            ResolverError::ThisOutsideClass(span) => {
+                Diagnostic::new("Can't use 'this' outside of a class.", Some(*span), self)
+            }
+            ResolverError::SuperOutsideClass(span) => {
+                Diagnostic::new("Can't use 'super' outside of a class.", Some(*span), self)
+            }
+            ResolverError::SuperWithoutSuperclass(span) => Diagnostic::new(
+                "Can't use 'super' in a class with no superclass.",
+                Some(*span),
+                self,
+            ),
+            ResolverError::InheritsFromItself { span, .. } => {
+                Diagnostic::new("A class can't inherit from itself.", Some(*span), self)
+            }
+        }
+    }
+}
+
+impl ToDiagnostic for EvaluationError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            EvaluationError::MustBeNumber(span) => {
+                Diagnostic::new("Operand must be a number.", Some(*span), self)
+            }
+            EvaluationError::OperandsMustBeNumbers(span) => {
+                Diagnostic::new("Operands must be numbers.", Some(*span), self)
+            }
+            EvaluationError::OperandsMustBeNumbersOrStrings(span) => Diagnostic::new(
+                "Operands must be two numbers or two strings.",
+                Some(*span),
+                self,
+            ),
+            EvaluationError::UndefinedVariable { name, span } => {
+                Diagnostic::new(format!("Undefined variable '{name}'."), Some(*span), self)
+                    .with_help(format!("declare it first with `var {name} = ...;`"))
+            }
+            EvaluationError::NotCallable(span) => {
+                Diagnostic::new("Can only call functions and classes.", Some(*span), self)
+            }
+            EvaluationError::ArityMismatch {
+                expected,
+                got,
+                span,
+            } => Diagnostic::new(
+                format!("Expected {expected} arguments but got {got}."),
+                Some(*span),
+                self,
+            ),
+            EvaluationError::UndefinedProperty { name, span } => {
+                Diagnostic::new(format!("Undefined property '{name}'."), Some(*span), self)
+            }
+            EvaluationError::OnlyInstancesHaveProperties(span) => {
+                Diagnostic::new("Only instances have properties.", Some(*span), self)
+            }
+            EvaluationError::OnlyInstancesHaveFields(span) => {
+                Diagnostic::new("Only instances have fields.", Some(*span), self)
+            }
+            EvaluationError::SuperclassMustBeClass(span) => {
+                Diagnostic::new("Superclass must be a class.", Some(*span), self)
+            }
+            EvaluationError::StackOverflow(span) => {
+                Diagnostic::new("Stack overflow.", Some(*span), self)
+                    .with_note("calls are nested too deeply, probably through unbounded recursion")
+            }
+            EvaluationError::Native { message, span } => {
+                Diagnostic::new(message.clone(), Some(*span), self)
+            }
+            EvaluationError::Output(err) => {
+                Diagnostic::new(format!("could not write output: {err}"), None, self)
+            }
+        }
+    }
+}
+
+/// Renders diagnostics for one source file.
+#[derive(Debug, Clone, Copy)]
+pub struct Renderer<'a> {
+    name: &'a str,
+    source: &'a str,
+    format: ErrorFormat,
+    color: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(name: &'a str, source: &'a str, format: ErrorFormat) -> Self {
+        Self {
+            name,
+            source,
+            format,
+            color: false,
+        }
+    }
+
+    pub fn with_color(self, color: bool) -> Self {
+        Self { color, ..self }
+    }
+
+    /// Prints `error` to stderr.
+    pub fn emit(&self, error: &impl ToDiagnostic) {
+        eprintln!("{}", self.render(&error.to_diagnostic()));
+    }
+
+    /// Prints where a runtime error happened to stderr, innermost frame
+    /// first. Long traces, as left by runaway recursion, are shortened.
+    pub fn emit_trace(&self, trace: &[TraceFrame]) {
+        eprint!("{}", self.render_trace(trace));
+    }
+
+    pub fn render_trace(&self, trace: &[TraceFrame]) -> String {
+        let mut out = String::new();
+        for frame in trace.iter().take(MAX_TRACE_FRAMES) {
+            let _ = match self.format {
+                ErrorFormat::Plain => {
+                    writeln!(out, "  in {} [line {}]", frame.description, frame.span.line)
+                }
+                ErrorFormat::Human => writeln!(
+                    out,
+                    "  {} {} {}",
+                    self.paint(BLUE, "in"),
+                    frame.description,
+                    self.paint(
+                        BLUE,
+                        &format!("at {}:{}:{}", self.name, frame.span.line, frame.span.column)
+                    )
+                ),
+            };
+        }
+        if trace.len() > MAX_TRACE_FRAMES {
+            let _ = writeln!(out, "  ... {} more frames", trace.len() - MAX_TRACE_FRAMES);
+        }
+        out
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        match self.format {
+            ErrorFormat::Plain => diagnostic.summary.clone(),
+            ErrorFormat::Human => self.render_human(diagnostic),
+        }
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{style}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn render_human(&self, diagnostic: &Diagnostic) -> String {
+        let mut out = format!(
+            "{}{}",
+            self.paint(RED, "error"),
+            self.paint(BOLD, &format!(": {}", diagnostic.message))
+        );
+
+        let gutter = match diagnostic.span {
+            Some(span) => {
+                let gutter = span.line.to_string().len();
+                let pad = " ".repeat(gutter);
+                let bar = self.paint(BLUE, "|");
+                let (line, prefix, width) = self.snippet(span);
+                let _ = write!(
+                    out,
+                    "\n{pad}{} {}:{}:{}",
+                    self.paint(BLUE, "-->"),
+                    self.name,
+                    span.line,
+                    span.column
+                );
+                let _ = write!(out, "\n{pad} {bar}");
+                let _ = write!(
+                    out,
+                    "\n{} {bar} {line}",
+                    self.paint(BLUE, &span.line.to_string())
+                );
+                let _ = write!(
+                    out,
+                    "\n{pad} {bar} {prefix}{}",
+                    self.paint(RED, &"^".repeat(width))
+                );
+                gutter
+            }
+            None => {
+                let _ = write!(out, "\n {} {}", self.paint(BLUE, "-->"), self.name);
+                1
+            }
+        };
+
+        let pad = " ".repeat(gutter);
+        for note in &diagnostic.notes {
+            let _ = write!(out, "\n{pad} {} {note}", self.paint(BOLD, "= note:"));
+        }
+        if let Some(help) = &diagnostic.help {
+            let _ = write!(out, "\n{pad} {} {help}", self.paint(BOLD, "= help:"));
+        }
+        out
+    }
+
+    /// Returns the source line containing `span`, the padding that lines a
+    /// caret up with the start of the span and the length of the underline.
+    fn snippet(&self, span: Span) -> (&'a str, String, usize) {
+        let start = span.start.min(self.source.len());
+        let line_start = self.source[..start].rfind('\n').map_or(0, |pos| pos + 1);
+        let line_end = self.source[start..]
+            .find('\n')
+            .map_or(self.source.len(), |pos| start + pos);
+        let line = self.source[line_start..line_end].trim_end_matches('\r');
+
+        let prefix = self.source[line_start..start]
+            .chars()
+            .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+            .collect();
+        let end = span.end.clamp(start, line_end);
+        let width = self.source[start..end].chars().count().max(1);
+        (line, prefix, width)
+    }
+}
@@ -0,0 +1,36 @@
+use thiserror::Error;
+
+use crate::{interpreter::EvaluationError, parser::error::ParserError, resolver::ResolverError};
+
+/// Any failure from running Lox source through [`crate::Interpreter`].
+#[derive(Debug, Error)]
+pub enum LoxError {
+    #[error("{}", join(_0))]
+    Syntax(Vec<ParserError>),
+
+    #[error("{}", join(_0))]
+    Resolve(Vec<ResolverError>),
+
+    #[error(transparent)]
+    Runtime(#[from] EvaluationError),
+}
+
+impl LoxError {
+    /// Process exit code the reference interpreter uses for this kind of error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            LoxError::Syntax(_) | LoxError::Resolve(_) => 65,
+            LoxError::Runtime(_) => 70,
+        }
+    }
+}
+
+fn join(errors: &[impl std::fmt::Display]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub type LoxResult<T> = std::result::Result<T, LoxError>;
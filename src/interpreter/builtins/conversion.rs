@@ -0,0 +1,35 @@
+//! Conversions between values and introspection of their types.
+
+use crate::interpreter::{
+    builtins::expected, EvaluationValue, Interpreter, NativeError, NativeResult,
+};
+
+pub(super) fn register(interpreter: &mut Interpreter) {
+    interpreter.define_native("str", 1, str);
+    interpreter.define_native("num", 1, num);
+    interpreter.define_native("type", 1, type_of);
+}
+
+/// The text `print` would show for the value.
+fn str(_: &mut Interpreter, args: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    Ok(EvaluationValue::Str(format!("{:?}", args[0])))
+}
+
+/// Parses a string as a number; numbers are returned unchanged. Only finite
+/// numbers are accepted, so `"inf"` and `"NaN"` are errors like in a literal.
+fn num(_: &mut Interpreter, args: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    match &args[0] {
+        EvaluationValue::Number(n) => Ok(EvaluationValue::Number(*n)),
+        EvaluationValue::Str(s) => match s.trim().parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(EvaluationValue::Number(n)),
+            _ => Err(NativeError::new(format!(
+                "num() could not parse \"{s}\" as a number."
+            ))),
+        },
+        _ => Err(expected("num", "a string or number", 0)),
+    }
+}
+
+fn type_of(_: &mut Interpreter, args: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    Ok(EvaluationValue::Str(args[0].type_name().to_string()))
+}
@@ -0,0 +1,80 @@
+//! Numeric builtins and the interpreter's random number generator.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::interpreter::{builtins::number, EvaluationValue, Interpreter, NativeResult};
+
+pub(super) fn register(interpreter: &mut Interpreter) {
+    interpreter.define_native("floor", 1, |_, args| unary("floor", args, f64::floor));
+    interpreter.define_native("ceil", 1, |_, args| unary("ceil", args, f64::ceil));
+    interpreter.define_native("sqrt", 1, |_, args| unary("sqrt", args, f64::sqrt));
+    interpreter.define_native("abs", 1, |_, args| unary("abs", args, f64::abs));
+    interpreter.define_native("pow", 2, |_, args| binary("pow", args, f64::powf));
+    interpreter.define_native("min", 2, |_, args| binary("min", args, f64::min));
+    interpreter.define_native("max", 2, |_, args| binary("max", args, f64::max));
+    interpreter.define_native("random", 0, random);
+    interpreter.define_native("seed", 1, seed);
+}
+
+fn unary(
+    name: &str,
+    args: &[EvaluationValue],
+    op: fn(f64) -> f64,
+) -> NativeResult<EvaluationValue> {
+    Ok(EvaluationValue::Number(op(number(name, args, 0)?)))
+}
+
+fn binary(
+    name: &str,
+    args: &[EvaluationValue],
+    op: fn(f64, f64) -> f64,
+) -> NativeResult<EvaluationValue> {
+    let (a, b) = (number(name, args, 0)?, number(name, args, 1)?);
+    Ok(EvaluationValue::Number(op(a, b)))
+}
+
+/// A number in `[0, 1)` from the interpreter's generator.
+fn random(interpreter: &mut Interpreter, _: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    Ok(EvaluationValue::Number(interpreter.random.next_f64()))
+}
+
+/// `seed(n)`: restarts the generator so the following `random()` calls
+/// repeat for the same `n`.
+fn seed(interpreter: &mut Interpreter, args: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    interpreter.seed_random(number("seed", args, 0)?.to_bits());
+    Ok(EvaluationValue::Nil)
+}
+
+/// SplitMix64 generator: small, fast and good enough for scripts.
+/// Not suitable for anything security related.
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Seeds from the system clock.
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Self::new(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`, using the top 53 bits of the next output.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
@@ -0,0 +1,60 @@
+//! Native functions defined in the globals of every [`Interpreter`].
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::interpreter::{EvaluationValue, Interpreter, NativeError, NativeResult};
+
+pub use math::Random;
+
+mod conversion;
+mod math;
+mod string;
+
+/// Registers every builtin as a global of `interpreter`.
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.define_native("clock", 0, clock);
+    conversion::register(interpreter);
+    math::register(interpreter);
+    string::register(interpreter);
+}
+
+/// Seconds since the Unix epoch.
+fn clock(_: &mut Interpreter, _: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| NativeError::new("System clock is set before the Unix epoch."))?;
+    Ok(EvaluationValue::Number(elapsed.as_secs_f64()))
+}
+
+fn expected(name: &str, what: &str, position: usize) -> NativeError {
+    NativeError::new(format!(
+        "{name}() expects {what} as argument {}.",
+        position + 1
+    ))
+}
+
+/// Reads argument `position` of `name` as a string.
+fn string<'a>(name: &str, args: &'a [EvaluationValue], position: usize) -> NativeResult<&'a str> {
+    match &args[position] {
+        EvaluationValue::Str(s) => Ok(s),
+        _ => Err(expected(name, "a string", position)),
+    }
+}
+
+/// Reads argument `position` of `name` as a number.
+fn number(name: &str, args: &[EvaluationValue], position: usize) -> NativeResult<f64> {
+    match args[position] {
+        EvaluationValue::Number(n) => Ok(n),
+        _ => Err(expected(name, "a number", position)),
+    }
+}
+
+/// Reads argument `position` of `name` as a non-negative whole number.
+fn index(name: &str, args: &[EvaluationValue], position: usize) -> NativeResult<usize> {
+    match args[position] {
+        EvaluationValue::Number(n) if n >= 0.0 && n.fract() == 0.0 && n <= usize::MAX as f64 => {
+            Ok(n as usize)
+        }
+        _ => Err(expected(name, "a non-negative integer", position)),
+    }
+}
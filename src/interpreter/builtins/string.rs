@@ -0,0 +1,105 @@
+//! String builtins. Positions and lengths count characters, not bytes.
+
+use std::rc::Rc;
+
+use crate::interpreter::{
+    builtins::{expected, index, string},
+    EvaluationValue, Interpreter, NativeError, NativeResult,
+};
+
+pub(super) fn register(interpreter: &mut Interpreter) {
+    interpreter.define_native("len", 1, len);
+    interpreter.define_native("substr", 3, substr);
+    interpreter.define_native("indexOf", 2, index_of);
+    interpreter.define_native("upper", 1, upper);
+    interpreter.define_native("lower", 1, lower);
+    interpreter.define_native("split", 2, split);
+    interpreter.define_native("trim", 1, trim);
+    interpreter.define_native("at", 2, at);
+}
+
+/// Number of characters in a string or elements in a list.
+fn len(_: &mut Interpreter, args: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    let len = match &args[0] {
+        EvaluationValue::Str(s) => s.chars().count(),
+        EvaluationValue::List(list) => list.len(),
+        _ => return Err(expected("len", "a string or list", 0)),
+    };
+    Ok(EvaluationValue::Number(len as f64))
+}
+
+/// `substr(s, start, end)`: the characters of `s` from `start` up to but
+/// not including `end`.
+fn substr(_: &mut Interpreter, args: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    let s = string("substr", args, 0)?;
+    let start = index("substr", args, 1)?;
+    let end = index("substr", args, 2)?;
+    let len = s.chars().count();
+    if start > end || end > len {
+        return Err(NativeError::new(format!(
+            "substr() range {start}..{end} is out of bounds for a string of length {len}."
+        )));
+    }
+    Ok(EvaluationValue::Str(
+        s.chars().skip(start).take(end - start).collect(),
+    ))
+}
+
+/// Character position of the first occurrence of `needle`, or -1.
+fn index_of(_: &mut Interpreter, args: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    let s = string("indexOf", args, 0)?;
+    let needle = string("indexOf", args, 1)?;
+    let position = s
+        .find(needle)
+        .map_or(-1.0, |byte| s[..byte].chars().count() as f64);
+    Ok(EvaluationValue::Number(position))
+}
+
+fn upper(_: &mut Interpreter, args: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    Ok(EvaluationValue::Str(
+        string("upper", args, 0)?.to_uppercase(),
+    ))
+}
+
+fn lower(_: &mut Interpreter, args: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    Ok(EvaluationValue::Str(
+        string("lower", args, 0)?.to_lowercase(),
+    ))
+}
+
+/// Splits on every occurrence of the separator, or into single characters
+/// when it is empty.
+fn split(_: &mut Interpreter, args: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    let s = string("split", args, 0)?;
+    let separator = string("split", args, 1)?;
+    let parts: Vec<_> = if separator.is_empty() {
+        s.chars()
+            .map(|ch| EvaluationValue::Str(ch.to_string()))
+            .collect()
+    } else {
+        s.split(separator)
+            .map(|part| EvaluationValue::Str(part.to_string()))
+            .collect()
+    };
+    Ok(EvaluationValue::List(Rc::new(parts)))
+}
+
+fn trim(_: &mut Interpreter, args: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    Ok(EvaluationValue::Str(
+        string("trim", args, 0)?.trim().to_string(),
+    ))
+}
+
+/// `at(list, i)`: element `i` of a list such as the one returned by `split`.
+fn at(_: &mut Interpreter, args: &[EvaluationValue]) -> NativeResult<EvaluationValue> {
+    let EvaluationValue::List(list) = &args[0] else {
+        return Err(expected("at", "a list", 0));
+    };
+    let i = index("at", args, 1)?;
+    list.get(i).cloned().ok_or_else(|| {
+        NativeError::new(format!(
+            "at() index {i} is out of bounds for a list of length {}.",
+            list.len()
+        ))
+    })
+}
@@ -0,0 +1,18 @@
+use crate::interpreter::{EvaluationResult, EvaluationValue, Interpreter};
+
+/// Anything Lox code can call: user-defined functions as well as functions
+/// implemented in Rust.
+///
+/// The interpreter checks the number of arguments against [`Callable::arity`]
+/// before calling [`Callable::call`].
+pub trait Callable: std::fmt::Debug {
+    fn name(&self) -> &str;
+
+    fn arity(&self) -> usize;
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        args: Vec<EvaluationValue>,
+    ) -> EvaluationResult<EvaluationValue>;
+}
@@ -0,0 +1,107 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    interpreter::{Callable, EvaluationResult, EvaluationValue, Interpreter, LoxFunction},
+    parser::expr::{Ident, IdentMap},
+};
+
+pub struct LoxClass {
+    pub name: String,
+    pub superclass: Option<Rc<LoxClass>>,
+    methods: IdentMap<Rc<LoxFunction>>,
+    /// Handle to the `Rc` owning this class, used to give instances a
+    /// reference back to it when the class is called.
+    this: Weak<LoxClass>,
+}
+
+impl LoxClass {
+    pub fn new(
+        name: String,
+        superclass: Option<Rc<LoxClass>>,
+        methods: IdentMap<Rc<LoxFunction>>,
+    ) -> Rc<Self> {
+        Rc::new_cyclic(|this| Self {
+            name,
+            superclass,
+            methods,
+            this: this.clone(),
+        })
+    }
+
+    /// Looks `name` up in this class and then along the superclass chain.
+    pub fn find_method(&self, name: &Ident) -> Option<Rc<LoxFunction>> {
+        match (self.methods.get(name), &self.superclass) {
+            (Some(method), _) => Some(method.clone()),
+            (None, Some(superclass)) => superclass.find_method(name),
+            (None, None) => None,
+        }
+    }
+}
+
+impl Callable for LoxClass {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn arity(&self) -> usize {
+        self.find_method(&Ident::init())
+            .map_or(0, |init| init.arity())
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        args: Vec<EvaluationValue>,
+    ) -> EvaluationResult<EvaluationValue> {
+        let class = self.this.upgrade().expect("class outlived its own Rc");
+        let instance = Rc::new(LoxInstance::new(class));
+        if let Some(init) = self.find_method(&Ident::init()) {
+            init.bind(instance.clone()).call(interpreter, args)?;
+        }
+        Ok(EvaluationValue::Instance(instance))
+    }
+}
+
+impl std::fmt::Debug for LoxClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+pub struct LoxInstance {
+    pub class: Rc<LoxClass>,
+    fields: RefCell<IdentMap<EvaluationValue>>,
+}
+
+impl LoxInstance {
+    pub fn new(class: Rc<LoxClass>) -> Self {
+        Self {
+            class,
+            fields: RefCell::new(IdentMap::default()),
+        }
+    }
+
+    /// Reads a field, falling back to a method bound to `this`.
+    pub fn get(self: &Rc<Self>, name: &Ident) -> Option<EvaluationValue> {
+        if let Some(value) = self.fields.borrow().get(name) {
+            return Some(value.clone());
+        }
+        let method = self.class.find_method(name)?;
+        Some(EvaluationValue::Callable(Rc::new(
+            method.bind(self.clone()),
+        )))
+    }
+
+    pub fn set(&self, name: Ident, value: EvaluationValue) {
+        self.fields.borrow_mut().insert(name, value);
+    }
+}
+
+impl std::fmt::Debug for LoxInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} instance", self.class.name)
+    }
+}
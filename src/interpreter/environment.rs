@@ -0,0 +1,80 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    interpreter::EvaluationValue,
+    parser::expr::{Ident, IdentMap},
+};
+
+/// Shared handle to an [`Environment`]; blocks and closures keep their parent alive.
+pub type Env = Rc<RefCell<Environment>>;
+
+/// A single lexical scope linked to the scope it is nested in.
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: IdentMap<EvaluationValue>,
+    enclosing: Option<Env>,
+}
+
+impl Environment {
+    pub fn new() -> Env {
+        Rc::new(RefCell::new(Self::default()))
+    }
+
+    pub fn with_enclosing(enclosing: Env) -> Env {
+        Rc::new(RefCell::new(Self {
+            values: IdentMap::default(),
+            enclosing: Some(enclosing),
+        }))
+    }
+
+    /// Binds `name` in this scope, shadowing any outer binding and
+    /// overwriting a previous one in the same scope.
+    pub fn define(&mut self, name: Ident, value: EvaluationValue) {
+        self.values.insert(name, value);
+    }
+
+    pub fn get(&self, name: &Ident) -> Option<EvaluationValue> {
+        match (self.values.get(name), &self.enclosing) {
+            (Some(value), _) => Some(value.clone()),
+            (None, Some(enclosing)) => enclosing.borrow().get(name),
+            (None, None) => None,
+        }
+    }
+
+    /// Reads `name` from the scope exactly `depth` levels up the chain.
+    pub fn get_at(&self, depth: usize, name: &Ident) -> Option<EvaluationValue> {
+        match (depth, &self.enclosing) {
+            (0, _) => self.values.get(name).cloned(),
+            (_, Some(enclosing)) => enclosing.borrow().get_at(depth - 1, name),
+            (_, None) => None,
+        }
+    }
+
+    /// Updates `name` in the scope exactly `depth` levels up the chain.
+    pub fn assign_at(&mut self, depth: usize, name: &Ident, value: EvaluationValue) -> bool {
+        match (depth, &self.enclosing) {
+            (0, _) => match self.values.get_mut(name) {
+                Some(slot) => {
+                    *slot = value;
+                    true
+                }
+                None => false,
+            },
+            (_, Some(enclosing)) => enclosing.borrow_mut().assign_at(depth - 1, name, value),
+            (_, None) => false,
+        }
+    }
+
+    /// Updates the nearest existing binding of `name`, returning `false`
+    /// when it is not bound in any enclosing scope.
+    pub fn assign(&mut self, name: &Ident, value: EvaluationValue) -> bool {
+        if let Some(slot) = self.values.get_mut(name) {
+            *slot = value;
+            return true;
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::process::Termination;
+
+use thiserror::Error;
+
+use crate::lexer::Span;
+
+#[derive(Debug, Error)]
+pub enum EvaluationError {
+    #[error("Operand must be a number.\n[line {}]", _0.line)]
+    MustBeNumber(Span),
+
+    #[error("Operands must be numbers.\n[line {}]", _0.line)]
+    OperandsMustBeNumbers(Span),
+
+    #[error("Operands must be two numbers or two strings.\n[line {}]", _0.line)]
+    OperandsMustBeNumbersOrStrings(Span),
+
+    #[error("Undefined variable '{name}'.\n[line {}]", span.line)]
+    UndefinedVariable { name: String, span: Span },
+
+    #[error("Can only call functions and classes.\n[line {}]", _0.line)]
+    NotCallable(Span),
+
+    #[error("Expected {expected} arguments but got {got}.\n[line {}]", span.line)]
+    ArityMismatch {
+        expected: usize,
+        got: usize,
+        span: Span,
+    },
+
+    #[error("Undefined property '{name}'.\n[line {}]", span.line)]
+    UndefinedProperty { name: String, span: Span },
+
+    #[error("Only instances have properties.\n[line {}]", _0.line)]
+    OnlyInstancesHaveProperties(Span),
+
+    #[error("Only instances have fields.\n[line {}]", _0.line)]
+    OnlyInstancesHaveFields(Span),
+
+    #[error("Superclass must be a class.\n[line {}]", _0.line)]
+    SuperclassMustBeClass(Span),
+
+    #[error("Stack overflow.\n[line {}]", _0.line)]
+    StackOverflow(Span),
+
+    #[error("{message}\n[line {}]", span.line)]
+    Native { message: String, span: Span },
+
+    #[error("could not write output: {0}")]
+    Output(#[from] std::io::Error),
+}
+
+pub type EvaluationResult<T> = std::result::Result<T, EvaluationError>;
+
+/// Failure reported by a native function. The interpreter turns it into
+/// [`EvaluationError::Native`] pointing at the call site.
+#[derive(Debug, Error)]
+#[error("{0}")]
+pub struct NativeError(pub String);
+
+impl NativeError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+pub type NativeResult<T> = std::result::Result<T, NativeError>;
+
+impl Termination for EvaluationError {
+    fn report(self) -> std::process::ExitCode {
+        70.into()
+    }
+}
@@ -0,0 +1,75 @@
+use std::{ops::ControlFlow, rc::Rc};
+
+use crate::{
+    interpreter::{
+        Callable, Env, Environment, EvaluationResult, EvaluationValue, Interpreter, LoxInstance,
+    },
+    parser::{expr::Ident, stmt::FunctionDecl},
+};
+
+/// A function declared in Lox code together with the environment it closes over.
+pub struct LoxFunction {
+    declaration: Rc<FunctionDecl>,
+    closure: Env,
+    /// `init` methods always return `this`, even from a bare `return;`.
+    is_initializer: bool,
+}
+
+impl LoxFunction {
+    pub fn new(declaration: Rc<FunctionDecl>, closure: Env, is_initializer: bool) -> Self {
+        Self {
+            declaration,
+            closure,
+            is_initializer,
+        }
+    }
+
+    /// Returns a copy of this method with `this` bound to `instance`.
+    pub fn bind(&self, instance: Rc<LoxInstance>) -> LoxFunction {
+        let environment = Environment::with_enclosing(self.closure.clone());
+        environment
+            .borrow_mut()
+            .define(Ident::this(), EvaluationValue::Instance(instance));
+        LoxFunction::new(self.declaration.clone(), environment, self.is_initializer)
+    }
+
+    fn this(&self) -> EvaluationValue {
+        self.closure
+            .borrow()
+            .get(&Ident::this())
+            .unwrap_or(EvaluationValue::Nil)
+    }
+}
+
+impl Callable for LoxFunction {
+    fn name(&self) -> &str {
+        self.declaration.name.as_str()
+    }
+
+    fn arity(&self) -> usize {
+        self.declaration.params.len()
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        args: Vec<EvaluationValue>,
+    ) -> EvaluationResult<EvaluationValue> {
+        let environment = Environment::with_enclosing(self.closure.clone());
+        for (param, arg) in self.declaration.params.iter().zip(args) {
+            environment.borrow_mut().define(param.clone(), arg);
+        }
+        let flow = interpreter.execute_block(&self.declaration.body, environment)?;
+        match flow {
+            _ if self.is_initializer => Ok(self.this()),
+            ControlFlow::Break(value) => Ok(value),
+            ControlFlow::Continue(()) => Ok(EvaluationValue::Nil),
+        }
+    }
+}
+
+impl std::fmt::Debug for LoxFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<fn {}>", self.name())
+    }
+}
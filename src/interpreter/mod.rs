@@ -0,0 +1,590 @@
+use std::{collections::HashMap, io::Write, ops::ControlFlow, rc::Rc};
+
+pub use builtins::Random;
+pub use callable::Callable;
+pub use class::{LoxClass, LoxInstance};
+pub use environment::{Env, Environment};
+pub use error::{EvaluationError, EvaluationResult, NativeError, NativeResult};
+pub use function::LoxFunction;
+pub use native::{NativeFn, NativeFunction};
+pub use value::EvaluationValue;
+
+use crate::{
+    error::{LoxError, LoxResult},
+    lexer::{Lexer, Span},
+    parser::{
+        expr::{BinaryKind, ExprId, ExprKind, Ident, IdentMap, LogicalKind, UnaryKind},
+        stmt::StmtKind,
+        Expr, Literal as LiteralType, Parser, Stmt,
+    },
+    resolver::Resolver,
+};
+
+pub mod builtins;
+pub mod callable;
+pub mod class;
+pub mod environment;
+pub mod error;
+pub mod function;
+pub mod native;
+pub mod value;
+
+/// How deep Lox calls may nest before the interpreter reports a stack overflow.
+const MAX_CALL_DEPTH: usize = 4096;
+
+/// Remaining native stack below which evaluation switches to a freshly
+/// allocated segment, and the size of that segment.
+const STACK_RED_ZONE: usize = 64 * 1024;
+const STACK_SEGMENT: usize = 2 * 1024 * 1024;
+
+/// Outcome of executing a statement: either carry on with the next one or
+/// unwind to the enclosing call with the returned value.
+pub type Flow = ControlFlow<EvaluationValue>;
+
+/// A function call currently being executed.
+#[derive(Debug, Clone)]
+pub struct CallFrame {
+    pub name: String,
+    pub span: Span,
+}
+
+/// A statement or call that was being executed when a runtime error
+/// unwound through it.
+#[derive(Debug, Clone)]
+pub struct TraceFrame {
+    pub description: String,
+    pub span: Span,
+}
+
+impl TraceFrame {
+    fn statement(stmt: &Stmt) -> Self {
+        let description = match &stmt.kind {
+            StmtKind::Expr(_) => "expression statement".to_string(),
+            StmtKind::Print(_) => "print statement".to_string(),
+            StmtKind::Declaration(name, _) => format!("declaration of '{}'", name),
+            StmtKind::Block(_) => "block".to_string(),
+            StmtKind::If { .. } => "if statement".to_string(),
+            StmtKind::While { .. } => "loop".to_string(),
+            StmtKind::Function(decl) => format!("declaration of '{}'", decl.name),
+            StmtKind::Return(_) => "return statement".to_string(),
+            StmtKind::Class { name, .. } => format!("class '{}'", name),
+        };
+        Self {
+            description,
+            span: stmt.span,
+        }
+    }
+}
+
+/// Executes the AST produced by [`crate::parser::Parser`].
+pub struct Interpreter {
+    pub(crate) globals: Env,
+    environment: Env,
+    /// Scope distance of every local variable reference, filled in by the
+    /// resolver. References missing from here are globals.
+    locals: HashMap<ExprId, usize>,
+    call_stack: Vec<CallFrame>,
+    /// Statements and calls the last runtime error unwound through,
+    /// innermost first.
+    trace: Vec<TraceFrame>,
+    /// Where `print` writes to.
+    output: Box<dyn Write>,
+    /// State behind the `random` builtin.
+    pub(crate) random: Random,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::with_output(std::io::stdout())
+    }
+}
+
+impl std::fmt::Debug for Interpreter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Interpreter")
+            .field("globals", &self.globals)
+            .field("environment", &self.environment)
+            .field("call_stack", &self.call_stack)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an interpreter whose `print` statements write to `output`.
+    pub fn with_output(output: impl Write + 'static) -> Self {
+        let globals = Environment::new();
+        let mut interpreter = Self {
+            environment: globals.clone(),
+            globals,
+            locals: HashMap::new(),
+            call_stack: vec![],
+            trace: vec![],
+            output: Box::new(output),
+            random: Random::from_time(),
+        };
+        builtins::register(&mut interpreter);
+        interpreter
+    }
+
+    pub fn set_output(&mut self, output: impl Write + 'static) {
+        self.output = Box::new(output);
+    }
+
+    /// Makes the sequence returned by the `random` builtin reproducible.
+    pub fn seed_random(&mut self, seed: u64) {
+        self.random = Random::new(seed);
+    }
+
+    /// Lexes, parses, resolves and runs `source` as a program. Globals it
+    /// defines stay visible to later calls.
+    pub fn run(&mut self, source: &str) -> LoxResult<()> {
+        let stmts = Parser::new(Lexer::from_source(source))
+            .parse()
+            .map_err(LoxError::Syntax)?;
+        self.run_stmts(&stmts)
+    }
+
+    /// Resolves and runs statements that were already parsed, such as one
+    /// entry at a time from a [`Parser`].
+    pub fn run_stmts(&mut self, stmts: &[Stmt]) -> LoxResult<()> {
+        Resolver::new(self)
+            .resolve(stmts)
+            .map_err(LoxError::Resolve)?;
+        Ok(self.interpret(stmts)?)
+    }
+
+    /// Evaluates `source` as a single expression in the global scope.
+    pub fn eval(&mut self, source: &str) -> LoxResult<EvaluationValue> {
+        let expr = Parser::new(Lexer::from_source(source))
+            .parse_expression()
+            .map_err(LoxError::Syntax)?;
+        self.eval_expr(&expr)
+    }
+
+    /// Resolves and evaluates an already parsed expression in the global
+    /// scope.
+    pub fn eval_expr(&mut self, expr: &Expr) -> LoxResult<EvaluationValue> {
+        Resolver::new(self)
+            .resolve_expression(expr)
+            .map_err(LoxError::Resolve)?;
+        Ok(self.evaluate_global(expr)?)
+    }
+
+    /// Defines or overwrites the global `name`.
+    pub fn define_global(&mut self, name: &str, value: impl Into<EvaluationValue>) {
+        self.globals
+            .borrow_mut()
+            .define(Ident::new(name), value.into());
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<EvaluationValue> {
+        self.globals.borrow().get(&Ident::new(name))
+    }
+
+    /// Exposes a Rust function to Lox as the global `name`. The interpreter
+    /// checks that it is called with exactly `arity` arguments.
+    pub fn define_native(
+        &mut self,
+        name: &str,
+        arity: usize,
+        function: impl Fn(&mut Interpreter, &[EvaluationValue]) -> NativeResult<EvaluationValue>
+            + 'static,
+    ) {
+        let native = NativeFunction::new(name, arity, function);
+        self.define_global(name, EvaluationValue::Callable(Rc::new(native)));
+    }
+
+    /// Records that the variable referenced by `id` lives `depth` scopes up.
+    pub(crate) fn resolve(&mut self, id: ExprId, depth: usize) {
+        self.locals.insert(id, depth);
+    }
+
+    fn interpret(&mut self, stmts: &[Stmt]) -> EvaluationResult<()> {
+        self.trace.clear();
+        for stmt in stmts {
+            if let ControlFlow::Break(_) = self.execute(stmt)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn evaluate_global(&mut self, expr: &Expr) -> EvaluationResult<EvaluationValue> {
+        self.trace.clear();
+        self.evaluate(expr)
+    }
+
+    /// Where the last runtime error happened, from the innermost statement
+    /// out to the top level.
+    pub fn trace(&self) -> &[TraceFrame] {
+        &self.trace
+    }
+
+    fn execute(&mut self, stmt: &Stmt) -> EvaluationResult<Flow> {
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || {
+            self.execute_statement(stmt)
+        });
+        if result.is_err() {
+            self.trace.push(TraceFrame::statement(stmt));
+        }
+        result
+    }
+
+    fn execute_statement(&mut self, stmt: &Stmt) -> EvaluationResult<Flow> {
+        match &stmt.kind {
+            StmtKind::Expr(expr) => {
+                self.evaluate(expr)?;
+            }
+            StmtKind::Print(expr) => {
+                let value = self.evaluate(expr)?;
+                writeln!(self.output, "{:?}", value)?;
+            }
+            StmtKind::Declaration(ident, initializer) => {
+                let value = self.evaluate(initializer)?;
+                self.environment.borrow_mut().define(ident.clone(), value);
+            }
+            StmtKind::Block(block) => {
+                let environment = Environment::with_enclosing(self.environment.clone());
+                return self.execute_block(block, environment);
+            }
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                if self.evaluate(condition)?.is_truthy() {
+                    return self.execute(then_branch);
+                } else if let Some(else_branch) = else_branch {
+                    return self.execute(else_branch);
+                }
+            }
+            StmtKind::While { condition, body } => {
+                while self.evaluate(condition)?.is_truthy() {
+                    if let ControlFlow::Break(value) = self.execute(body)? {
+                        return Ok(ControlFlow::Break(value));
+                    }
+                }
+            }
+            StmtKind::Function(decl) => {
+                let function = LoxFunction::new(decl.clone(), self.environment.clone(), false);
+                self.environment.borrow_mut().define(
+                    decl.name.clone(),
+                    EvaluationValue::Callable(Rc::new(function)),
+                );
+            }
+            StmtKind::Return(expr) => {
+                let value = match expr {
+                    Some(expr) => self.evaluate(expr)?,
+                    None => EvaluationValue::Nil,
+                };
+                return Ok(ControlFlow::Break(value));
+            }
+            StmtKind::Class {
+                name,
+                superclass,
+                methods,
+            } => {
+                let superclass = match superclass {
+                    Some(expr) => match self.evaluate(expr)? {
+                        EvaluationValue::Class(class) => Some(class),
+                        _ => return Err(EvaluationError::SuperclassMustBeClass(expr.span)),
+                    },
+                    None => None,
+                };
+
+                // Methods of a subclass close over an extra scope holding `super`.
+                let closure = match &superclass {
+                    Some(superclass) => {
+                        let environment = Environment::with_enclosing(self.environment.clone());
+                        environment
+                            .borrow_mut()
+                            .define(Ident::super_(), EvaluationValue::Class(superclass.clone()));
+                        environment
+                    }
+                    None => self.environment.clone(),
+                };
+                let methods = methods
+                    .iter()
+                    .map(|method| {
+                        let is_initializer = method.name.as_str() == "init";
+                        let function =
+                            LoxFunction::new(method.clone(), closure.clone(), is_initializer);
+                        (method.name.clone(), Rc::new(function))
+                    })
+                    .collect::<IdentMap<_>>();
+
+                let class = LoxClass::new(name.to_string(), superclass, methods);
+                self.environment
+                    .borrow_mut()
+                    .define(name.clone(), EvaluationValue::Class(class));
+            }
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
+    /// Runs `stmts` inside `environment`, restoring the current one afterwards
+    /// even when a statement fails or returns.
+    pub(crate) fn execute_block(
+        &mut self,
+        stmts: &[Stmt],
+        environment: Env,
+    ) -> EvaluationResult<Flow> {
+        let previous = std::mem::replace(&mut self.environment, environment);
+        let mut result = Ok(ControlFlow::Continue(()));
+        for stmt in stmts {
+            result = self.execute(stmt);
+            if !matches!(result, Ok(ControlFlow::Continue(()))) {
+                break;
+            }
+        }
+        self.environment = previous;
+        result
+    }
+
+    fn lookup(&self, name: &Ident, expr: &Expr) -> EvaluationResult<EvaluationValue> {
+        self.lookup_at(name, self.locals.get(&expr.id).copied(), expr.span)
+    }
+
+    fn lookup_at(
+        &self,
+        name: &Ident,
+        depth: Option<usize>,
+        span: Span,
+    ) -> EvaluationResult<EvaluationValue> {
+        let value = match depth {
+            Some(depth) => self.environment.borrow().get_at(depth, name),
+            None => self.globals.borrow().get(name),
+        };
+        value.ok_or_else(|| EvaluationError::UndefinedVariable {
+            name: name.to_string(),
+            span,
+        })
+    }
+
+    /// Span of the call currently being executed, used to locate errors
+    /// raised by native functions.
+    pub(crate) fn call_site(&self) -> Span {
+        self.call_stack
+            .last()
+            .map(|frame| frame.span)
+            .unwrap_or_default()
+    }
+
+    fn call(
+        &mut self,
+        callable: Rc<dyn Callable>,
+        args: Vec<EvaluationValue>,
+        span: Span,
+    ) -> EvaluationResult<EvaluationValue> {
+        if args.len() != callable.arity() {
+            return Err(EvaluationError::ArityMismatch {
+                expected: callable.arity(),
+                got: args.len(),
+                span,
+            });
+        }
+        if self.call_stack.len() >= MAX_CALL_DEPTH {
+            return Err(EvaluationError::StackOverflow(span));
+        }
+        self.call_stack.push(CallFrame {
+            name: callable.name().to_string(),
+            span,
+        });
+        let result = callable.call(self, args);
+        if let Some(frame) = self.call_stack.pop().filter(|_| result.is_err()) {
+            self.trace.push(TraceFrame {
+                description: format!("call to '{}'", frame.name),
+                span: frame.span,
+            });
+        }
+        result
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> EvaluationResult<EvaluationValue> {
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || self.evaluate_expr(expr))
+    }
+
+    fn evaluate_expr(&mut self, expr: &Expr) -> EvaluationResult<EvaluationValue> {
+        match &expr.kind {
+            ExprKind::Literal(literal) => match literal {
+                LiteralType::Str(s) => Ok(EvaluationValue::Str(s.to_string())),
+                LiteralType::Number(n) => Ok(EvaluationValue::Number(*n)),
+                LiteralType::Logical(l) => Ok(EvaluationValue::Logical(*l)),
+                LiteralType::Nil => Ok(EvaluationValue::Nil),
+            },
+            ExprKind::Unary {
+                op,
+                op_span,
+                operand,
+            } => {
+                let value = self.evaluate(operand)?;
+                match op {
+                    UnaryKind::Negation => match value {
+                        EvaluationValue::Number(n) => Ok(EvaluationValue::Number(-n)),
+                        _ => Err(EvaluationError::MustBeNumber(*op_span)),
+                    },
+                    UnaryKind::LogicalNot => Ok(EvaluationValue::Logical(!value.is_truthy())),
+                }
+            }
+            ExprKind::Binary {
+                op,
+                op_span,
+                left,
+                right,
+            } => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                match (op, left, right) {
+                    (
+                        BinaryKind::Multiplication,
+                        EvaluationValue::Number(left),
+                        EvaluationValue::Number(right),
+                    ) => Ok(EvaluationValue::Number(left * right)),
+                    (
+                        BinaryKind::Division,
+                        EvaluationValue::Number(left),
+                        EvaluationValue::Number(right),
+                    ) => Ok(EvaluationValue::Number(left / right)),
+                    (
+                        BinaryKind::Addition,
+                        EvaluationValue::Number(left),
+                        EvaluationValue::Number(right),
+                    ) => Ok(EvaluationValue::Number(left + right)),
+                    (
+                        BinaryKind::Subtraction,
+                        EvaluationValue::Number(left),
+                        EvaluationValue::Number(right),
+                    ) => Ok(EvaluationValue::Number(left - right)),
+                    (
+                        BinaryKind::Addition,
+                        EvaluationValue::Str(left),
+                        EvaluationValue::Str(right),
+                    ) => Ok(EvaluationValue::Str(left + &right)),
+
+                    (
+                        BinaryKind::Less,
+                        EvaluationValue::Number(left),
+                        EvaluationValue::Number(right),
+                    ) => Ok(EvaluationValue::Logical(left < right)),
+                    (
+                        BinaryKind::LessEqual,
+                        EvaluationValue::Number(left),
+                        EvaluationValue::Number(right),
+                    ) => Ok(EvaluationValue::Logical(left <= right)),
+                    (
+                        BinaryKind::Greater,
+                        EvaluationValue::Number(left),
+                        EvaluationValue::Number(right),
+                    ) => Ok(EvaluationValue::Logical(left > right)),
+                    (
+                        BinaryKind::GreaterEqual,
+                        EvaluationValue::Number(left),
+                        EvaluationValue::Number(right),
+                    ) => Ok(EvaluationValue::Logical(left >= right)),
+                    (BinaryKind::Equality, left, right) => {
+                        Ok(EvaluationValue::Logical(left == right))
+                    }
+                    (BinaryKind::NotEquality, left, right) => {
+                        Ok(EvaluationValue::Logical(left != right))
+                    }
+                    (BinaryKind::Addition, ..) => {
+                        Err(EvaluationError::OperandsMustBeNumbersOrStrings(*op_span))
+                    }
+                    _ => Err(EvaluationError::OperandsMustBeNumbers(*op_span)),
+                }
+            }
+            ExprKind::Logical { op, left, right } => {
+                let left = self.evaluate(left)?;
+                let short_circuits = match op {
+                    LogicalKind::Or => left.is_truthy(),
+                    LogicalKind::And => !left.is_truthy(),
+                };
+                if short_circuits {
+                    Ok(left)
+                } else {
+                    self.evaluate(right)
+                }
+            }
+            ExprKind::Group(group) => self.evaluate(group),
+            ExprKind::Call { callee, args } => {
+                let callee = self.evaluate(callee)?;
+                let mut values = Vec::with_capacity(args.len());
+                for arg in args {
+                    values.push(self.evaluate(arg)?);
+                }
+                match callee {
+                    EvaluationValue::Callable(callable) => self.call(callable, values, expr.span),
+                    EvaluationValue::Class(class) => self.call(class, values, expr.span),
+                    _ => Err(EvaluationError::NotCallable(expr.span)),
+                }
+            }
+            ExprKind::Get { object, name } => match self.evaluate(object)? {
+                EvaluationValue::Instance(instance) => {
+                    instance
+                        .get(name)
+                        .ok_or_else(|| EvaluationError::UndefinedProperty {
+                            name: name.to_string(),
+                            span: expr.span,
+                        })
+                }
+                _ => Err(EvaluationError::OnlyInstancesHaveProperties(expr.span)),
+            },
+            ExprKind::Set {
+                object,
+                name,
+                value,
+            } => {
+                let EvaluationValue::Instance(instance) = self.evaluate(object)? else {
+                    return Err(EvaluationError::OnlyInstancesHaveFields(expr.span));
+                };
+                let value = self.evaluate(value)?;
+                instance.set(name.clone(), value.clone());
+                Ok(value)
+            }
+            ExprKind::This => self.lookup(&Ident::this(), expr),
+            ExprKind::Super { method } => {
+                // `this` is bound one scope inside the one holding `super`.
+                let depth = self.locals.get(&expr.id).copied();
+                let EvaluationValue::Class(superclass) = self.lookup(&Ident::super_(), expr)?
+                else {
+                    return Err(EvaluationError::SuperclassMustBeClass(expr.span));
+                };
+                let EvaluationValue::Instance(instance) =
+                    self.lookup_at(&Ident::this(), depth.map(|depth| depth - 1), expr.span)?
+                else {
+                    return Err(EvaluationError::OnlyInstancesHaveProperties(expr.span));
+                };
+                let method = superclass.find_method(method).ok_or_else(|| {
+                    EvaluationError::UndefinedProperty {
+                        name: method.to_string(),
+                        span: expr.span,
+                    }
+                })?;
+                Ok(EvaluationValue::Callable(Rc::new(method.bind(instance))))
+            }
+            ExprKind::Ident(ident) => self.lookup(ident, expr),
+            ExprKind::Assignment(left, right) => {
+                let value = self.evaluate(right)?;
+                let assigned = match self.locals.get(&expr.id) {
+                    Some(depth) => {
+                        self.environment
+                            .borrow_mut()
+                            .assign_at(*depth, left, value.clone())
+                    }
+                    None => self.globals.borrow_mut().assign(left, value.clone()),
+                };
+                if !assigned {
+                    return Err(EvaluationError::UndefinedVariable {
+                        name: left.to_string(),
+                        span: expr.span,
+                    });
+                }
+                Ok(value)
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::interpreter::{
+    Callable, EvaluationError, EvaluationResult, EvaluationValue, Interpreter, NativeResult,
+};
+
+/// Signature of a function implemented in Rust and callable from Lox.
+pub type NativeFn = dyn Fn(&mut Interpreter, &[EvaluationValue]) -> NativeResult<EvaluationValue>;
+
+/// A host function exposed to Lox code under `name`.
+pub struct NativeFunction {
+    name: String,
+    arity: usize,
+    function: Box<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new(
+        name: impl Into<String>,
+        arity: usize,
+        function: impl Fn(&mut Interpreter, &[EvaluationValue]) -> NativeResult<EvaluationValue>
+            + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            arity,
+            function: Box::new(function),
+        }
+    }
+}
+
+impl Callable for NativeFunction {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        args: Vec<EvaluationValue>,
+    ) -> EvaluationResult<EvaluationValue> {
+        (self.function)(interpreter, &args).map_err(|err| EvaluationError::Native {
+            message: err.0,
+            span: interpreter.call_site(),
+        })
+    }
+}
+
+impl std::fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn>")
+    }
+}
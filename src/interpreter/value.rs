@@ -0,0 +1,107 @@
+use std::rc::Rc;
+
+use crate::{
+    interpreter::{Callable, LoxClass, LoxInstance},
+    number,
+};
+
+#[derive(Clone)]
+pub enum EvaluationValue {
+    Nil,
+    Number(f64),
+    Str(String),
+    Logical(bool),
+    Callable(Rc<dyn Callable>),
+    Class(Rc<LoxClass>),
+    Instance(Rc<LoxInstance>),
+    /// Immutable sequence produced by builtins such as `split`.
+    List(Rc<Vec<EvaluationValue>>),
+}
+
+impl EvaluationValue {
+    /// Lox truthiness: `nil` and `false` are falsey, everything else is truthy.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, EvaluationValue::Nil | EvaluationValue::Logical(false))
+    }
+
+    /// Name of the value's type as reported by the `type` builtin.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Nil => "nil",
+            Self::Number(_) => "number",
+            Self::Str(_) => "string",
+            Self::Logical(_) => "boolean",
+            Self::Callable(_) => "function",
+            Self::Class(_) => "class",
+            Self::Instance(_) => "instance",
+            Self::List(_) => "list",
+        }
+    }
+}
+
+/// Lox `==`. Values of different types are never equal, numbers follow
+/// IEEE 754 (so `NaN != NaN`) and functions, classes, instances and lists
+/// are equal only to themselves.
+impl PartialEq for EvaluationValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Nil, Self::Nil) => true,
+            (Self::Number(a), Self::Number(b)) => a == b,
+            (Self::Str(a), Self::Str(b)) => a == b,
+            (Self::Logical(a), Self::Logical(b)) => a == b,
+            (Self::Callable(a), Self::Callable(b)) => Rc::ptr_eq(a, b),
+            (Self::Class(a), Self::Class(b)) => Rc::ptr_eq(a, b),
+            (Self::Instance(a), Self::Instance(b)) => Rc::ptr_eq(a, b),
+            (Self::List(a), Self::List(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl From<f64> for EvaluationValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<bool> for EvaluationValue {
+    fn from(value: bool) -> Self {
+        Self::Logical(value)
+    }
+}
+
+impl From<&str> for EvaluationValue {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+impl From<String> for EvaluationValue {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl std::fmt::Debug for EvaluationValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Nil => write!(f, "nil"),
+            Self::Number(arg0) => write!(f, "{}", number::display(*arg0)),
+            Self::Str(arg0) => write!(f, "{arg0}"),
+            Self::Logical(arg0) => write!(f, "{arg0}"),
+            Self::Callable(callable) => write!(f, "{callable:?}"),
+            Self::Class(class) => write!(f, "{class:?}"),
+            Self::Instance(instance) => write!(f, "{instance:?}"),
+            Self::List(list) => {
+                write!(f, "[")?;
+                for (i, value) in list.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{value:?}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
@@ -1,15 +1,40 @@
 use thiserror::Error;
 
+use crate::lexer::Span;
+
 #[derive(Debug, Error)]
 pub enum LexerError {
-    #[error("could not parse source")]
+    #[error("could not read source: {0}")]
     SourceError(#[from] tokio::io::Error),
 
-    #[error("[line {line}] Error: Unexpected character: {ch}")]
-    UnexpectedCharacter { line: usize, ch: char },
+    #[error("[line {}] Error: Unexpected character: {ch}", span.line)]
+    UnexpectedCharacter { span: Span, ch: char },
+
+    #[error("[line {}] Error: Unterminated string.", _0.line)]
+    UnterminatedString(Span),
+
+    #[error("[line {}] Error: Invalid escape sequence: {sequence}", span.line)]
+    InvalidEscape { span: Span, sequence: String },
+
+    #[error("[line {}] Error: Malformed number: {lexeme}", span.line)]
+    MalformedNumber { span: Span, lexeme: String },
+
+    #[error("[line {}] Error: Unterminated block comment.", _0.line)]
+    UnterminatedBlockComment(Span),
+}
 
-    #[error("[line {_0}] Error: Unterminated string.")]
-    UnterminatedString(usize),
+impl LexerError {
+    /// Where the error is in the source, if it is about one place.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            LexerError::SourceError(_) => None,
+            LexerError::UnexpectedCharacter { span, .. }
+            | LexerError::InvalidEscape { span, .. }
+            | LexerError::MalformedNumber { span, .. }
+            | LexerError::UnterminatedString(span)
+            | LexerError::UnterminatedBlockComment(span) => Some(*span),
+        }
+    }
 }
 
 pub type LexerResult<T> = std::result::Result<T, LexerError>;
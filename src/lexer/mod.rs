@@ -1,9 +1,11 @@
-#![allow(dead_code, unused)]
-
 pub mod error;
 pub mod scanner;
+pub mod source;
+pub mod span;
 pub mod tokens;
 
 pub use error::*;
-pub use scanner::Lexer;
+pub use scanner::{open_delimiters, Lexer};
+pub use source::Source;
+pub use span::Span;
 pub use tokens::*;
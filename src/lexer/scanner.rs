@@ -1,24 +1,46 @@
-use crate::lexer::{tokens::RESERVED_WORDS, LexerError, LexerResult, Token};
+use std::borrow::Cow;
 
+use crate::lexer::{tokens::RESERVED_WORDS, LexerError, Span, Token};
+
+/// Splits source text into [`Token`]s that borrow from it.
 #[derive(Debug)]
-pub struct Lexer {
-    pub source: String,
+pub struct Lexer<'src> {
+    pub source: &'src str,
     pub current_line: usize,
-    pub result: LexerResult<()>,
+    pub errors: Vec<LexerError>,
     cursor: usize,
+    /// Byte offset of `source` within the whole input, added to every span.
+    offset: usize,
     done: bool,
+    /// A byte offset on the current line and its column in characters,
+    /// so columns are counted from the last token rather than the line start.
+    column_mark: (usize, usize),
+    token_start: usize,
+    token_line: usize,
+    token_column: usize,
 }
 
-impl Lexer {
-    pub async fn new(filename: &str) -> LexerResult<Self> {
-        let source = tokio::fs::read_to_string(filename).await?;
-        Ok(Lexer {
+impl<'src> Lexer<'src> {
+    pub fn from_source(source: &'src str) -> Self {
+        Self::from_source_at(source, 1, 0)
+    }
+
+    /// Lexes `source` as a piece of a larger input that starts at the
+    /// beginning of `line`, `offset` bytes in. Spans point into the whole
+    /// input.
+    pub fn from_source_at(source: &'src str, line: usize, offset: usize) -> Self {
+        Lexer {
             source,
             cursor: 0,
-            current_line: 1,
-            result: Ok(()),
+            offset,
+            current_line: line,
+            errors: vec![],
             done: false,
-        })
+            column_mark: (0, 1),
+            token_start: 0,
+            token_line: line,
+            token_column: 1,
+        }
     }
 
     fn peek_char(&self) -> char {
@@ -26,169 +48,384 @@ impl Lexer {
     }
 
     fn advance(&mut self) {
-        self.cursor += 1;
+        self.cursor += self.peek_char().len_utf8();
     }
 
-    fn starts_with(&self, s: &str) -> bool {
-        self.source[self.cursor..].starts_with(s)
+    fn newline(&mut self) {
+        self.current_line += 1;
+        self.column_mark = (self.cursor, 1);
     }
 
-    pub fn parse_to_end(&mut self) -> Vec<Token> {
-        self.by_ref().collect()
+    /// Column of the cursor, counted in characters.
+    fn column(&mut self) -> usize {
+        let (offset, column) = self.column_mark;
+        let column = column + self.source[offset..self.cursor].chars().count();
+        self.column_mark = (self.cursor, column);
+        column
     }
-}
 
-fn is_number(ch: char) -> bool {
-    ch.is_ascii_digit() || ch == '.'
-}
+    fn token_span(&self) -> Span {
+        Span::new(
+            self.offset + self.token_start,
+            self.offset + self.cursor,
+            self.token_line,
+            self.token_column,
+        )
+    }
 
-fn is_alpha(c: char) -> bool {
-    c.is_ascii_lowercase() || c.is_ascii_uppercase() || c == '_'
-}
+    fn unexpected_character(&mut self, ch: char) {
+        let span = self.token_span();
+        self.errors
+            .push(LexerError::UnexpectedCharacter { span, ch });
+    }
 
-pub const UNEXPECTED: &[char] = &['#', '$', '%', '@'];
+    fn starts_with(&self, s: &str) -> bool {
+        self.source[self.cursor..].starts_with(s)
+    }
 
-impl Iterator for Lexer {
-    type Item = Token;
+    /// Skips a `/* ... */` comment, including any comments nested in it,
+    /// after its opening `/`. Returns `false` if the source ends first.
+    fn block_comment(&mut self) -> bool {
+        self.advance();
+        let mut depth = 1;
+        while self.cursor < self.source.len() {
+            if self.starts_with("/*") {
+                self.cursor += 2;
+                depth += 1;
+            } else if self.starts_with("*/") {
+                self.cursor += 2;
+                depth -= 1;
+                if depth == 0 {
+                    return true;
+                }
+            } else {
+                let ch = self.peek_char();
+                self.advance();
+                if ch == '\n' {
+                    self.newline();
+                }
+            }
+        }
+        false
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.cursor >= self.source.len() {
-            if !self.done {
-                self.done = true;
-                return Some(Token::EOF);
+    /// Scans the rest of a string literal after its opening `"`, returning
+    /// its value with escapes resolved, or `None` if the source ends first.
+    /// The value borrows from the source unless there are escapes.
+    fn string(&mut self) -> Option<Cow<'src, str>> {
+        let source = self.source;
+        let start = self.cursor;
+        let mut escaped: Option<String> = None;
+        while self.cursor < source.len() {
+            let ch = self.peek_char();
+            let end = self.cursor;
+            self.advance();
+            match ch {
+                '"' => return Some(escaped.map_or(Cow::Borrowed(&source[start..end]), Cow::Owned)),
+                '\\' => {
+                    let value = escaped.get_or_insert_with(|| source[start..end].to_string());
+                    value.extend(self.escape());
+                }
+                ch => {
+                    if ch == '\n' {
+                        self.newline();
+                    }
+                    if let Some(value) = &mut escaped {
+                        value.push(ch);
+                    }
+                }
             }
+        }
+        None
+    }
+
+    /// Resolves the escape sequence after a `\`, recording an error if it is
+    /// not a valid one.
+    fn escape(&mut self) -> Option<char> {
+        if self.cursor >= self.source.len() {
             return None;
         }
+        let start = self.cursor - 1;
+        let column = self.column() - 1;
+        let ch = self.peek_char();
+        if ch != '\n' {
+            self.advance();
+        }
+        let escaped = match ch {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            '"' | '\\' => Some(ch),
+            'u' => self.unicode_escape(),
+            _ => None,
+        };
+        if escaped.is_none() {
+            let span = Span::new(
+                self.offset + start,
+                self.offset + self.cursor,
+                self.current_line,
+                column,
+            );
+            let sequence = self.source[start..self.cursor].to_string();
+            self.errors
+                .push(LexerError::InvalidEscape { span, sequence });
+        }
+        escaped
+    }
 
-        let mut ch = self.peek_char();
+    /// Reads the `{...}` of a `\u{...}` escape: one to six hex digits naming
+    /// a Unicode scalar value.
+    fn unicode_escape(&mut self) -> Option<char> {
+        if !self.starts_with("{") {
+            return None;
+        }
         self.advance();
-        while UNEXPECTED.contains(&ch) {
-            self.result = Err(LexerError::UnexpectedCharacter {
-                line: self.current_line,
-                ch,
-            });
-            eprintln!(
-                "[line {}] Error: Unexpected character: {}",
-                self.current_line, ch
-            );
+        let digits = self.source[self.cursor..]
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or(self.source.len() - self.cursor);
+        let hex = &self.source[self.cursor..self.cursor + digits];
+        let escaped = match hex.len() {
+            1..=6 => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+            _ => None,
+        };
+        self.cursor += digits;
+        if !self.starts_with("}") {
+            return None;
+        }
+        self.advance();
+        escaped
+    }
 
-            if self.source[self.cursor..].is_empty() {
-                self.done = true;
-                return Some(Token::EOF);
+    /// Scans the rest of a number literal starting with the digit `first`,
+    /// returning its value or `None` if it is malformed.
+    ///
+    /// Decimals may have a fraction, where the dot must be followed by a
+    /// digit, and an exponent. `0x` and `0b` start hexadecimal and binary
+    /// integers. Digits may be grouped with single `_` separators.
+    fn number(&mut self, first: char) -> Option<f64> {
+        let radix = match self.source[self.cursor..].chars().next() {
+            Some('x' | 'X') if first == '0' => 16,
+            Some('b' | 'B') if first == '0' => 2,
+            _ => 10,
+        };
+        if radix != 10 {
+            self.advance();
+            let digits = self.digits(radix);
+            let trailing = self.digits(36);
+            if digits.is_empty() || digits.starts_with('_') || !trailing.is_empty() {
+                return None;
             }
-            ch = self.peek_char();
+            return separated(digits).then(|| {
+                digits
+                    .chars()
+                    .filter_map(|ch| ch.to_digit(radix))
+                    .fold(0.0, |value, digit| value * radix as f64 + digit as f64)
+            });
+        }
+
+        self.cursor = self.token_start;
+        let mut valid = separated(self.digits(10));
+        if self.starts_with(".") && self.next_is_digit(1) {
             self.advance();
+            valid &= separated(self.digits(10));
         }
+        if self.starts_with("e") || self.starts_with("E") {
+            // An exponent marker with no digits after it, like `1e` or
+            // `2.5e-`, is part of the malformed number rather than a name.
+            let sign = usize::from(self.starts_with_sign(1));
+            valid &= self.next_is_digit(1 + sign);
+            self.cursor += 1 + sign;
+            valid &= separated(self.digits(10));
+        }
+        if !valid {
+            return None;
+        }
+        let text = &self.source[self.token_start..self.cursor];
+        if text.contains('_') {
+            text.replace('_', "").parse().ok()
+        } else {
+            text.parse().ok()
+        }
+    }
+
+    /// Consumes a run of digits in `radix` and `_` separators.
+    fn digits(&mut self, radix: u32) -> &'src str {
+        let start = self.cursor;
+        let len = self.source[start..]
+            .find(|c: char| !c.is_digit(radix) && c != '_')
+            .unwrap_or(self.source.len() - start);
+        self.cursor += len;
+        &self.source[start..self.cursor]
+    }
 
-        match ch {
-            '(' => Some(Token::left_paren()),
-            ')' => Some(Token::right_paren()),
-            '{' => Some(Token::left_brace()),
-            '}' => Some(Token::right_brace()),
-            '*' => Some(Token::star()),
-            '.' => Some(Token::dot()),
-            ',' => Some(Token::comma()),
-            '+' => Some(Token::plus()),
-            '-' => Some(Token::minus()),
-            ';' => Some(Token::semicolon()),
-            '/' if self.starts_with("/") => {
-                if let Some(pos) = self.source[self.cursor..].find('\n') {
-                    self.cursor += pos + 1;
-                    self.current_line += 1;
-                    self.next()
-                } else {
-                    self.cursor = self.source.len();
+    fn next_is_digit(&self, offset: usize) -> bool {
+        self.source
+            .as_bytes()
+            .get(self.cursor + offset)
+            .is_some_and(u8::is_ascii_digit)
+    }
+
+    fn starts_with_sign(&self, offset: usize) -> bool {
+        matches!(
+            self.source.as_bytes().get(self.cursor + offset),
+            Some(b'+' | b'-')
+        )
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    pub fn parse_to_end(&mut self) -> Vec<Token<'src>> {
+        self.by_ref().collect()
+    }
+}
+
+/// Counts braces and parentheses opened but not yet closed in `source`,
+/// ignoring any inside strings and comments. Negative when more are closed
+/// than opened.
+pub fn open_delimiters(source: &str) -> isize {
+    Lexer::from_source(source)
+        .map(|token| token.kind.nesting())
+        .sum()
+}
+
+/// Whether the `_` separators in a run of digits each sit between two digits.
+fn separated(digits: &str) -> bool {
+    !digits.ends_with('_') && !digits.contains("__")
+}
+
+/// Identifiers start with `_` or a character that may start a Unicode
+/// identifier (UAX #31), and continue with any identifier character.
+fn is_identifier_start(c: char) -> bool {
+    c == '_' || unicode_ident::is_xid_start(c)
+}
+
+pub const UNEXPECTED: &[char] = &['#', '$', '%', '@'];
+
+impl<'src> Iterator for Lexer<'src> {
+    type Item = Token<'src>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.token_start = self.cursor;
+            self.token_line = self.current_line;
+            self.token_column = self.column();
+
+            if self.cursor >= self.source.len() {
+                if !self.done {
                     self.done = true;
-                    Some(Token::EOF)
+                    return Some(Token::EOF.with_span(self.token_span()));
                 }
+                return None;
             }
-            '/' => Some(Token::slash()),
-            '=' if self.starts_with("=") => {
-                self.advance();
-                Some(Token::equal_equal())
-            }
-            '=' => Some(Token::equal()),
-            '!' if self.starts_with("=") => {
-                self.advance();
-                Some(Token::bang_equal())
-            }
-            '!' => Some(Token::bang()),
-            '>' if self.starts_with("=") => {
-                self.advance();
-                Some(Token::greater_equal())
-            }
-            '>' => Some(Token::greater()),
-            '<' if self.starts_with("=") => {
-                self.advance();
-                Some(Token::less_equal())
+
+            let ch = self.peek_char();
+            self.advance();
+            if UNEXPECTED.contains(&ch) {
+                self.unexpected_character(ch);
+                continue;
             }
-            '<' => Some(Token::less()),
-            '"' => {
-                if let Some(pos) = self.source[self.cursor..].find('"') {
-                    let end_pos = self.cursor + pos + 1;
-                    let s = &self.source[self.cursor - 1..end_pos];
-                    self.cursor = end_pos;
-                    Some(Token::string_literal(s.to_string()))
-                } else {
-                    self.cursor = self.source.len();
-                    self.result = Err(LexerError::UnterminatedString(self.current_line));
-                    eprintln!("[line {}] Error: Unterminated string.", self.current_line);
-                    self.done = true;
-                    Some(Token::EOF)
+
+            let token = match ch {
+                '(' => Token::left_paren(),
+                ')' => Token::right_paren(),
+                '{' => Token::left_brace(),
+                '}' => Token::right_brace(),
+                '*' => Token::star(),
+                '.' => Token::dot(),
+                ',' => Token::comma(),
+                '+' => Token::plus(),
+                '-' => Token::minus(),
+                ';' => Token::semicolon(),
+                '/' if self.starts_with("/") => {
+                    if let Some(pos) = self.source[self.cursor..].find('\n') {
+                        self.cursor += pos + 1;
+                        self.newline();
+                    } else {
+                        self.cursor = self.source.len();
+                    }
+                    continue;
                 }
-            }
-            ch if ch.is_whitespace() => {
-                if ch == '\n' {
-                    self.current_line += 1;
+                '/' if self.starts_with("*") => {
+                    if !self.block_comment() {
+                        let span = self.token_span();
+                        self.errors.push(LexerError::UnterminatedBlockComment(span));
+                    }
+                    continue;
                 }
-                self.next()
-            }
-            ch if ch.is_ascii_digit() => {
-                if let Some(pos) = self.source[self.cursor..].find(|c| !is_number(c)) {
-                    let end_pos = self.cursor + pos;
-                    let s = &self.source[self.cursor - 1..end_pos];
-                    self.cursor = end_pos;
-                    Some(Token::number_literal(s.to_string()))
-                } else {
-                    let s = &self.source[self.cursor - 1..];
-                    self.cursor = self.source.len();
-                    Some(Token::number_literal(s.to_string()))
+                '/' => Token::slash(),
+                '=' if self.starts_with("=") => {
+                    self.advance();
+                    Token::equal_equal()
                 }
-            }
-
-            ch if is_alpha(ch) => {
-                if let Some(pos) =
-                    self.source[self.cursor..].find(|c: char| !is_alpha(c) && !c.is_alphanumeric())
-                {
-                    let end_pos = self.cursor + pos;
-                    let s = &self.source[self.cursor - 1..end_pos];
-                    self.cursor = end_pos;
-                    if RESERVED_WORDS.contains(&s) {
-                        Some(Token::reserved(s.to_string()))
+                '=' => Token::equal(),
+                '!' if self.starts_with("=") => {
+                    self.advance();
+                    Token::bang_equal()
+                }
+                '!' => Token::bang(),
+                '>' if self.starts_with("=") => {
+                    self.advance();
+                    Token::greater_equal()
+                }
+                '>' => Token::greater(),
+                '<' if self.starts_with("=") => {
+                    self.advance();
+                    Token::less_equal()
+                }
+                '<' => Token::less(),
+                '"' => {
+                    if let Some(value) = self.string() {
+                        let lexeme = &self.source[self.token_start..self.cursor];
+                        Token::string_literal(lexeme, value)
                     } else {
-                        Some(Token::identifier(s.to_string()))
+                        let span = self.token_span();
+                        self.errors.push(LexerError::UnterminatedString(span));
+                        continue;
+                    }
+                }
+                ch if ch.is_whitespace() => {
+                    if ch == '\n' {
+                        self.newline();
+                    }
+                    continue;
+                }
+                ch if ch.is_ascii_digit() => {
+                    let value = self.number(ch);
+                    let lexeme = &self.source[self.token_start..self.cursor];
+                    match value {
+                        Some(value) => Token::number_literal(lexeme, value),
+                        None => {
+                            let span = self.token_span();
+                            let lexeme = lexeme.to_string();
+                            self.errors
+                                .push(LexerError::MalformedNumber { span, lexeme });
+                            continue;
+                        }
                     }
-                } else {
-                    let s = &self.source[self.cursor - 1..];
-                    self.cursor = self.source.len();
+                }
+
+                ch if is_identifier_start(ch) => {
+                    let end_pos = self.source[self.cursor..]
+                        .find(|c: char| !unicode_ident::is_xid_continue(c))
+                        .map_or(self.source.len(), |pos| self.cursor + pos);
+                    let s = &self.source[self.token_start..end_pos];
+                    self.cursor = end_pos;
                     if RESERVED_WORDS.contains(&s) {
-                        Some(Token::reserved(s.to_string()))
+                        Token::reserved(s)
                     } else {
-                        Some(Token::identifier(s.to_string()))
+                        Token::identifier(s)
                     }
                 }
-            }
-            _ => {
-                self.result = Err(LexerError::UnexpectedCharacter {
-                    line: self.current_line,
-                    ch,
-                });
-                eprintln!(
-                    "[line {}] Error: Unexpected character: {}",
-                    self.current_line, ch
-                );
-                None
-            }
+                _ => {
+                    self.unexpected_character(ch);
+                    continue;
+                }
+            };
+
+            return Some(token.with_span(self.token_span()));
         }
     }
 }
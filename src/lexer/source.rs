@@ -0,0 +1,62 @@
+use std::io;
+
+use tokio::io::AsyncReadExt;
+
+use crate::lexer::{Lexer, LexerResult};
+
+/// Filename that makes [`Source::read`] read standard input.
+pub const STDIN: &str = "-";
+
+/// Text of a Lox program and where it came from. [`Lexer`]s and their
+/// tokens borrow from it.
+#[derive(Debug, Clone)]
+pub struct Source {
+    /// Shown in diagnostics.
+    pub name: String,
+    pub text: String,
+}
+
+impl Source {
+    pub fn new(name: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            text: text.into(),
+        }
+    }
+
+    /// Reads all of the file at `filename`, or standard input until it is
+    /// closed when `filename` is `-`. The whole text is kept in memory; see
+    /// [`crate::parser::StreamParser`] for parsing input as it arrives.
+    /// Sources that are not valid UTF-8 are rejected with the position of
+    /// the first bad byte.
+    pub async fn read(filename: &str) -> LexerResult<Self> {
+        let (name, bytes) = if filename == STDIN {
+            let mut bytes = vec![];
+            tokio::io::stdin().read_to_end(&mut bytes).await?;
+            ("<stdin>", bytes)
+        } else {
+            (filename, tokio::fs::read(filename).await?)
+        };
+        let text = String::from_utf8(bytes).map_err(|err| {
+            let valid = &err.as_bytes()[..err.utf8_error().valid_up_to()];
+            let valid = std::str::from_utf8(valid).unwrap_or_default();
+            let line = valid.matches('\n').count() + 1;
+            let column = valid
+                .rsplit('\n')
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .count()
+                + 1;
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{name} is not valid UTF-8 (line {line}, column {column})"),
+            )
+        })?;
+        Ok(Self::new(name, text))
+    }
+
+    pub fn lexer(&self) -> Lexer<'_> {
+        Lexer::from_source(&self.text)
+    }
+}
@@ -1,15 +1,27 @@
-#![allow(dead_code, unused)]
+use std::borrow::Cow;
+
+use crate::{lexer::Span, number};
 
 pub const RESERVED_WORDS: &[&str] = &[
     "and", "class", "else", "false", "for", "fun", "if", "nil", "or", "print", "return", "super",
     "this", "true", "var", "while",
 ];
 
-#[derive(Clone, PartialEq, PartialOrd, Eq, Ord)]
-pub struct Token {
+/// A token whose text is borrowed from the source it was lexed from.
+#[derive(Clone, PartialEq)]
+pub struct Token<'src> {
     pub kind: TokenKind,
-    pub lexeme: String,
-    pub literal: Option<String>,
+    pub lexeme: &'src str,
+    pub literal: Option<TokenLiteral<'src>>,
+    pub span: Span,
+}
+
+/// Value of a string or number token.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenLiteral<'src> {
+    /// The text between the quotes, only copied when it contains escapes.
+    Str(Cow<'src, str>),
+    Number(f64),
 }
 
 macro_rules! new_token {
@@ -17,19 +29,21 @@ macro_rules! new_token {
         pub fn $name() -> Self {
             Self {
                 kind: $kind,
-                lexeme: $lex.to_string(),
+                lexeme: $lex,
                 literal: None,
+                span: Span::default(),
             }
         }
     };
 }
 
 use TokenKind::*;
-impl Token {
+impl<'src> Token<'src> {
     pub const EOF: Self = Self {
         kind: TokenKind::Eof,
-        lexeme: String::new(),
+        lexeme: "",
         literal: None,
+        span: Span::new(0, 0, 0, 0),
     };
 
     new_token!(left_paren, "(", LeftParen);
@@ -52,47 +66,38 @@ impl Token {
     new_token!(greater, ">", Greater);
     new_token!(greater_equal, ">=", GreaterEqual);
 
-    pub fn string_literal(s: String) -> Self {
+    /// A string token spelled `lexeme` in the source, quotes and escapes
+    /// included, whose resolved text is `value`.
+    pub fn string_literal(lexeme: &'src str, value: Cow<'src, str>) -> Self {
         Self {
             kind: TokenKind::StringLiteral,
-            lexeme: s.clone(),
-            literal: Some(
-                s.strip_prefix('"')
-                    .and_then(|s| s.strip_suffix('"'))
-                    .unwrap()
-                    .to_string(),
-            ),
+            lexeme,
+            literal: Some(TokenLiteral::Str(value)),
+            span: Span::default(),
         }
     }
 
-    pub fn number_literal(s: String) -> Self {
-        let mut literal = s.clone();
-        if !literal.contains('.') {
-            literal += ".0";
-        } else if let Some(zero_pos) = literal.find(".") {
-            literal = literal.trim_end_matches('0').to_string();
-
-            if literal.ends_with('.') {
-                literal += "0";
-            }
-        }
+    /// A number token spelled `lexeme` in the source with the given value.
+    pub fn number_literal(lexeme: &'src str, value: f64) -> Self {
         Self {
             kind: TokenKind::NumberLiteral,
-            lexeme: s,
-            literal: Some(literal),
+            lexeme,
+            literal: Some(TokenLiteral::Number(value)),
+            span: Span::default(),
         }
     }
 
-    pub fn identifier(s: String) -> Self {
+    pub fn identifier(lexeme: &'src str) -> Self {
         Self {
             kind: TokenKind::Identifier,
-            lexeme: s,
+            lexeme,
             literal: None,
+            span: Span::default(),
         }
     }
 
-    pub fn reserved(s: String) -> Self {
-        let kind = match s.as_str() {
+    pub fn reserved(lexeme: &'src str) -> Self {
+        let kind = match lexeme {
             "and" => AND,
             "class" => CLASS,
             "else" => ELSE,
@@ -113,24 +118,46 @@ impl Token {
         };
         Self {
             kind,
-            lexeme: s,
+            lexeme,
             literal: None,
+            span: Span::default(),
+        }
+    }
+
+    pub fn with_span(self, span: Span) -> Self {
+        Self { span, ..self }
+    }
+
+    /// The literal as the `tokenize` command shows it, `null` for tokens
+    /// without one.
+    ///
+    /// Plain decimals keep their digits, like `42.0` for `42`; other
+    /// spellings such as `0xFF` or `1e3` show the value the way the AST
+    /// printer does.
+    pub fn literal_text(&self) -> Cow<'src, str> {
+        match &self.literal {
+            None => Cow::Borrowed("null"),
+            Some(TokenLiteral::Str(value)) => value.clone(),
+            Some(TokenLiteral::Number(_))
+                if self.lexeme.chars().all(|c| c.is_ascii_digit() || c == '.') =>
+            {
+                match self.lexeme.split_once('.') {
+                    None => Cow::Owned(format!("{}.0", self.lexeme)),
+                    Some((whole, fraction)) => {
+                        let fraction = fraction.trim_end_matches('0');
+                        let fraction = if fraction.is_empty() { "0" } else { fraction };
+                        Cow::Owned(format!("{whole}.{fraction}"))
+                    }
+                }
+            }
+            Some(TokenLiteral::Number(value)) => Cow::Owned(number::literal(*value)),
         }
     }
 }
 
-impl std::fmt::Debug for Token {
+impl std::fmt::Debug for Token<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{:?} {} {}",
-            self.kind,
-            self.lexeme,
-            match self.literal {
-                Some(ref l) => l,
-                None => "null",
-            }
-        )
+        write!(f, "{:?} {} {}", self.kind, self.lexeme, self.literal_text())
     }
 }
 #[allow(clippy::upper_case_acronyms)]
@@ -177,6 +204,18 @@ pub enum TokenKind {
     Eof,
 }
 
+impl TokenKind {
+    /// `1` for a token that opens a brace or parenthesis, `-1` for one that
+    /// closes it.
+    pub fn nesting(self) -> isize {
+        match self {
+            LeftBrace | LeftParen => 1,
+            RightBrace | RightParen => -1,
+            _ => 0,
+        }
+    }
+}
+
 impl std::fmt::Debug for TokenKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -0,0 +1,35 @@
+//! A tree-walking interpreter for Lox.
+//!
+//! [`Interpreter`] is the entry point for hosting Lox scripts. Output from
+//! `print` goes to stdout unless another writer is passed to
+//! [`Interpreter::with_output`].
+//!
+//! ```
+//! use codecrafters_interpreter::{interpreter::NativeError, EvaluationValue, Interpreter};
+//!
+//! let mut lox = Interpreter::new();
+//! lox.define_global("name", "world");
+//! lox.define_native("twice", 1, |_, args| match &args[0] {
+//!     EvaluationValue::Number(n) => Ok(EvaluationValue::Number(n * 2.0)),
+//!     _ => Err(NativeError::new("twice() expects a number.")),
+//! });
+//! lox.run(r#"var greeting = "hello " + name; var answer = twice(21);"#)
+//!     .unwrap();
+//!
+//! assert!(matches!(lox.get_global("answer"), Some(EvaluationValue::Number(n)) if n == 42.0));
+//! assert!(matches!(lox.eval("greeting").unwrap(), EvaluationValue::Str(s) if s == "hello world"));
+//!
+//! let err = lox.run("\nprint twice(greeting);").unwrap_err();
+//! assert_eq!(err.to_string(), "twice() expects a number.\n[line 2]");
+//! ```
+
+pub mod diagnostics;
+pub mod error;
+pub mod interpreter;
+pub mod lexer;
+pub mod number;
+pub mod parser;
+pub mod resolver;
+
+pub use error::{LoxError, LoxResult};
+pub use interpreter::{EvaluationError, EvaluationValue, Interpreter};
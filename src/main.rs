@@ -1,95 +1,115 @@
-#![allow(dead_code, unused)]
-
 use clap::Parser as ClapParser;
-use std::process::exit;
+use std::{io::IsTerminal, process::exit};
 
 use cli::*;
-use lexer::Lexer;
-
-use crate::parser::{Expr, Parser, Stmt};
+use codecrafters_interpreter::{
+    diagnostics::{ErrorFormat, Renderer, ToDiagnostic},
+    lexer::{source::STDIN, Source},
+    parser::Parser,
+    Interpreter, LoxError,
+};
 
 mod cli;
-mod lexer;
-mod parser;
+mod repl;
+
+fn renderer<'a>(filename: &'a str, source: &'a str, format: ErrorFormat) -> Renderer<'a> {
+    let color = format == ErrorFormat::Human && std::io::stdout().is_terminal();
+    Renderer::new(filename, source, format).with_color(color)
+}
+
+/// Emits every syntax or resolution error and exits with the static error code.
+fn static_errors(renderer: &Renderer, errors: &[impl ToDiagnostic]) -> ! {
+    for err in errors {
+        renderer.emit(err);
+    }
+    exit(65);
+}
+
+/// Reports an error from resolving or running a program and exits with the
+/// matching code.
+fn lox_error(renderer: &Renderer, interpreter: &Interpreter, err: LoxError) -> ! {
+    match err {
+        LoxError::Syntax(errors) => static_errors(renderer, &errors),
+        LoxError::Resolve(errors) => static_errors(renderer, &errors),
+        LoxError::Runtime(err) => {
+            renderer.emit(&err);
+            renderer.emit_trace(interpreter.trace());
+            exit(70);
+        }
+    }
+}
+
+/// Reads the input, reporting a source that cannot be read and exiting.
+async fn source(input: &Input, format: ErrorFormat) -> Source {
+    input.source().await.unwrap_or_else(|err| {
+        let name = input.filename.as_deref().unwrap_or(STDIN);
+        renderer(name, "", format).emit(&err);
+        exit(1);
+    })
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
+    let format = args
+        .error_format
+        .unwrap_or(if std::io::stdout().is_terminal() {
+            ErrorFormat::Human
+        } else {
+            ErrorFormat::Plain
+        });
     match args.command {
-        Commands::Tokenize { filename } => {
-            let mut lexer = Lexer::new(&filename).await?;
+        Commands::Tokenize(input) => {
+            let source = source(&input, format).await;
+            let mut lexer = source.lexer();
             for token in lexer.by_ref() {
                 println!("{:?}", token);
             }
-            if lexer.result.is_err() {
-                exit(65);
-            }
-        }
-        Commands::Parse { filename } => {
-            let lexer = Lexer::new(&filename).await?;
-            let mut parser = Parser::new(lexer);
-            for expr in parser.by_ref() {
-                println!("{:?}", expr);
+            let renderer = renderer(&source.name, &source.text, format);
+            for err in &lexer.errors {
+                renderer.emit(err);
             }
-            if parser.result.is_err() {
+            if lexer.has_errors() {
                 exit(65);
             }
         }
-
-        Commands::Evaluate { filename } => {
-            let lexer = Lexer::new(&filename).await?;
-            let mut parser = Parser::new(lexer);
-            loop {
-                let Some(stmt) = parser.next() else {
-                    break;
-                };
-                if let Stmt::Expr(expr) = stmt {
-                    match expr.evaluate(&parser.global_scope) {
-                        Ok(res) => {
-                            println!("{:?}", res);
-                        }
-                        Err(err) => {
-                            eprintln!("{}", err);
-                            exit(70);
-                        }
-                    }
-                }
-            }
-            if parser.result.is_err() {
-                exit(65);
+        Commands::Parse(input) => {
+            let source = source(&input, format).await;
+            let renderer = renderer(&source.name, &source.text, format);
+            let mut parser = Parser::new(source.lexer());
+            match parser.parse_expression() {
+                Ok(expr) => println!("{:?}", expr),
+                Err(errors) => static_errors(&renderer, &errors),
             }
         }
-        Commands::Run { filename } => {
-            let lexer = Lexer::new(&filename).await?;
-            let mut parser = Parser::new(lexer);
-            loop {
-                let Some(stmt) = parser.next() else {
-                    break;
-                };
-                if let Err(parser::error::ParserError::UndefinedVariable(_)) = parser.result {
-                    exit(70);
-                    break;
-                }
-                match stmt.run(&parser.global_scope) {
-                    Ok(_) => {}
-                    Err(err) => {
-                        eprintln!("{}", err);
-                        exit(70);
-                    }
-                }
+        Commands::Evaluate(input) => {
+            let source = source(&input, format).await;
+            let renderer = renderer(&source.name, &source.text, format);
+            let mut parser = Parser::new(source.lexer());
+            let expr = match parser.parse_expression() {
+                Ok(expr) => expr,
+                Err(errors) => static_errors(&renderer, &errors),
+            };
+            let mut interpreter = Interpreter::new();
+            match interpreter.eval_expr(&expr) {
+                Ok(res) => println!("{:?}", res),
+                Err(err) => lox_error(&renderer, &interpreter, err),
             }
-
-            if let Err(err) = parser.result {
-                match err {
-                    parser::error::ParserError::UndefinedVariable(_) => {
-                        exit(70);
-                    }
-                    _ => {
-                        exit(65);
-                    }
-                }
+        }
+        Commands::Run(input) => {
+            let source = source(&input, format).await;
+            let renderer = renderer(&source.name, &source.text, format);
+            let mut parser = Parser::new(source.lexer());
+            let stmts = match parser.parse() {
+                Ok(stmts) => stmts,
+                Err(errors) => static_errors(&renderer, &errors),
+            };
+            let mut interpreter = Interpreter::new();
+            if let Err(err) = interpreter.run_stmts(&stmts) {
+                lox_error(&renderer, &interpreter, err);
             }
         }
+        Commands::Repl => repl::run(format)?,
     }
     Ok(())
 }
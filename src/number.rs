@@ -0,0 +1,44 @@
+//! Number to text conversion matching the reference implementation, which
+//! relies on Java's `Double.toString`.
+
+/// Numbers at or above this magnitude are written in scientific notation.
+const SCIENTIFIC_ABOVE: f64 = 1e7;
+/// Non-zero numbers below this magnitude are written in scientific notation.
+const SCIENTIFIC_BELOW: f64 = 1e-3;
+
+/// Formats `n` the way `Double.toString` does: the shortest digits that
+/// round-trip, always with a fractional part, switching to `1.5E-5` style
+/// outside `[1e-3, 1e7)`. Used when printing number literals in the AST.
+pub fn literal(n: f64) -> String {
+    if n.is_nan() {
+        return "NaN".to_string();
+    }
+    if n.is_infinite() {
+        return if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+
+    let magnitude = n.abs();
+    if magnitude != 0.0 && !(SCIENTIFIC_BELOW..SCIENTIFIC_ABOVE).contains(&magnitude) {
+        let formatted = format!("{n:e}");
+        let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+        let fraction = if mantissa.contains('.') { "" } else { ".0" };
+        return format!("{mantissa}{fraction}E{exponent}");
+    }
+
+    let formatted = n.to_string();
+    if formatted.contains('.') {
+        formatted
+    } else {
+        formatted + ".0"
+    }
+}
+
+/// Formats `n` for `print` and `str()`: like [`literal`], but whole
+/// numbers lose their trailing `.0`.
+pub fn display(n: f64) -> String {
+    let mut text = literal(n);
+    if text.ends_with(".0") {
+        text.truncate(text.len() - 2);
+    }
+    text
+}
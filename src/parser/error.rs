@@ -1,43 +1,42 @@
-use std::process::Termination;
-
 use thiserror::Error;
 
+use crate::lexer::Span;
+
 #[derive(Debug, Error)]
 pub enum ParserError {
-    #[error("[line {line}] Error: Expected expression: {lexeme}")]
-    ExpectedExpression { line: usize, lexeme: String },
+    #[error("[line {}] Error {}: Expect expression.", span.line, at(lexeme))]
+    ExpectedExpression { span: Span, lexeme: String },
 
     #[error("Lexer Error")]
     LexerError(#[from] crate::lexer::error::LexerError),
 
-    #[error("[line {_0}] Error: Unmatched parens")]
-    UnmatchedParens(usize),
-
-    #[error("[line {_0}] Error: Unexpected token")]
-    UnexpectedToken(usize),
-
-    #[error("Undefined variable '{0}'.")]
-    UndefinedVariable(String),
-}
+    #[error("[line {}] Error: Unmatched parens", _0.line)]
+    UnmatchedParens(Span),
 
-pub type ParserResult<T> = std::result::Result<T, ParserError>;
+    #[error("[line {}] Error: Unexpected token", _0.line)]
+    UnexpectedToken(Span),
 
-#[derive(Debug, Error)]
-pub enum EvaluationError {
-    #[error("Operand must be a number.\n[line 1]")]
-    MustBeNumber(usize),
+    #[error("[line {}] Error {}: {message}", span.line, at(lexeme))]
+    Expected {
+        span: Span,
+        lexeme: String,
+        message: &'static str,
+    },
 
-    #[error("Operands must be a number.\n[line 1]")]
-    OperandsMustBeNumber(usize),
+    #[error("[line {}] Error at '=': Invalid assignment target.", _0.line)]
+    InvalidAssignmentTarget(Span),
 
-    #[error("Undefined variable '{0}'.")]
-    UndefinedVariable(String),
+    #[error("[line {}] Error {}: Too much nesting.", span.line, at(lexeme))]
+    TooDeeplyNested { span: Span, lexeme: String },
 }
 
-pub type EvaluationResult<T> = std::result::Result<T, EvaluationError>;
-
-impl Termination for EvaluationError {
-    fn report(self) -> std::process::ExitCode {
-        65.into()
+/// Describes where an error occurred the way the reference implementation does.
+fn at(lexeme: &str) -> String {
+    if lexeme.is_empty() {
+        "at end".to_string()
+    } else {
+        format!("at '{lexeme}'")
     }
 }
+
+pub type ParserResult<T> = std::result::Result<T, ParserError>;
@@ -1,24 +1,170 @@
-use std::{collections::BTreeMap, ops::Deref};
+use std::{
+    collections::HashMap,
+    hash::{BuildHasherDefault, DefaultHasher, Hash, Hasher},
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
-use crate::parser::{
-    error::{EvaluationError, EvaluationResult}, stmt::Scope, Literal as LiteralType
+use crate::{
+    lexer::Span,
+    parser::{Literal as LiteralType, STACK_RED_ZONE, STACK_SEGMENT},
 };
 
-#[derive(Debug, Clone, PartialEq, PartialOrd, Ord, Eq)]
-pub struct Ident(pub String);
+thread_local! {
+    static THIS: Ident = Ident::new("this");
+    static SUPER: Ident = Ident::new("super");
+    static INIT: Ident = Ident::new("init");
+}
 
+/// A shared, reference counted name. The parser hands out one `Ident` per
+/// distinct name in a source, so cloning is a count bump and comparing
+/// equal names is usually a pointer check. The hash is computed once, so
+/// environment and field lookups do not rehash the text. The string is
+/// freed with the last tree or environment that uses it.
 #[derive(Clone)]
-pub enum Expr {
+pub struct Ident {
+    name: Rc<str>,
+    hash: u64,
+}
+
+impl Ident {
+    pub fn new(name: &str) -> Self {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        Self {
+            name: name.into(),
+            hash: hasher.finish(),
+        }
+    }
+
+    /// `this`, allocated once per thread for the interpreter's hot paths.
+    pub fn this() -> Self {
+        THIS.with(Ident::clone)
+    }
+
+    pub fn super_() -> Self {
+        SUPER.with(Ident::clone)
+    }
+
+    pub fn init() -> Self {
+        INIT.with(Ident::clone)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+}
+
+impl PartialEq for Ident {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && (Rc::ptr_eq(&self.name, &other.name) || self.name == other.name)
+    }
+}
+
+impl Eq for Ident {}
+
+impl Hash for Ident {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+/// A map keyed by [`Ident`] that reuses the hash each name already holds.
+pub type IdentMap<V> = HashMap<Ident, V, BuildHasherDefault<IdentHasher>>;
+
+/// Hasher for [`IdentMap`]: an [`Ident`] writes its stored hash, which is
+/// passed through unchanged.
+#[derive(Default)]
+pub struct IdentHasher(u64);
+
+impl Hasher for IdentHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0.rotate_left(5) ^ u64::from(byte)).wrapping_mul(0x517c_c1b7_2722_0a95);
+        }
+    }
+
+    fn write_u64(&mut self, hash: u64) {
+        self.0 = hash;
+    }
+}
+
+impl std::fmt::Debug for Ident {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.name)
+    }
+}
+
+impl std::fmt::Display for Ident {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// Unique identity of an expression node, used by the resolver to record
+/// where each variable reference is bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExprId(usize);
+
+impl ExprId {
+    fn next() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+#[derive(Clone)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+    pub id: ExprId,
+}
+
+#[derive(Clone)]
+pub enum ExprKind {
     Literal(LiteralType),
-    Unary(UnaryKind, Box<Expr>),
+    Unary {
+        op: UnaryKind,
+        /// Where the operator is, for runtime errors about its operand.
+        op_span: Span,
+        operand: Box<Expr>,
+    },
     Binary {
         op: BinaryKind,
+        /// Where the operator is, for runtime errors about its operands.
+        op_span: Span,
         left: Box<Expr>,
         right: Box<Expr>,
     },
-    Group(Vec<Expr>),
+    Logical {
+        op: LogicalKind,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    Group(Box<Expr>),
     Ident(Ident),
     Assignment(Ident, Box<Expr>),
+    Call {
+        callee: Box<Expr>,
+        args: Vec<Expr>,
+    },
+    Get {
+        object: Box<Expr>,
+        name: Ident,
+    },
+    Set {
+        object: Box<Expr>,
+        name: Ident,
+        value: Box<Expr>,
+    },
+    This,
+    Super {
+        method: Ident,
+    },
 }
 
 #[derive(Clone)]
@@ -67,191 +213,106 @@ impl std::fmt::Debug for BinaryKind {
     }
 }
 
-pub enum EvaluationValue {
-    Nil,
-    Number(f64),
-    Str(String),
-    Logical(bool),
-    Void,
+#[derive(Clone)]
+pub enum LogicalKind {
+    And,
+    Or,
 }
 
-impl EvaluationValue {
-    pub fn to_expr(&self) -> Expr {
+impl std::fmt::Debug for LogicalKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            EvaluationValue::Nil => Expr::NIL,
-            EvaluationValue::Number(n) => Expr::Literal(LiteralType::Number(*n)),
-            EvaluationValue::Str(s) => Expr::Literal(LiteralType::Str(s.to_owned())),
-            EvaluationValue::Logical(l) => Expr::Literal(LiteralType::Logical(*l)),
-            EvaluationValue::Void => todo!(),
+            Self::And => write!(f, "and"),
+            Self::Or => write!(f, "or"),
         }
     }
 }
 
-impl std::fmt::Debug for EvaluationValue {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Nil => write!(f, "nil"),
-            Self::Number(arg0) => write!(f, "{arg0}"),
-            Self::Str(arg0) => write!(f, "{arg0}"),
-            Self::Logical(arg0) => write!(f, "{arg0}"),
-            Self::Void => Ok(()),
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            id: ExprId::next(),
         }
     }
+
+    pub fn nil(span: Span) -> Self {
+        Self::new(ExprKind::Literal(LiteralType::Nil), span)
+    }
 }
 
-impl Expr {
-    pub const NIL: Self = Self::Literal(LiteralType::Nil);
+impl std::fmt::Debug for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || self.kind.fmt(f))
+    }
+}
 
-    pub fn evaluate(
-        &self,
-        scope: &Scope,
-    ) -> EvaluationResult<EvaluationValue> {
-        match self {
-            Expr::Literal(literal) => match literal {
-                LiteralType::Str(s) => Ok(EvaluationValue::Str(s.to_string())),
-                LiteralType::Number(n) => Ok(EvaluationValue::Number(*n)),
-                LiteralType::Logical(l) => Ok(EvaluationValue::Logical(*l)),
-                LiteralType::Nil => Ok(EvaluationValue::Nil),
-            },
-            Expr::Unary(unary_kind, expr) => {
-                let value = expr.evaluate(scope)?;
-                match (unary_kind, value) {
-                    (UnaryKind::Negation, EvaluationValue::Nil) => todo!(),
-                    (_, EvaluationValue::Void) => todo!(),
-                    (UnaryKind::Negation, EvaluationValue::Number(n)) => {
-                        Ok(EvaluationValue::Number(-n))
-                    }
-                    (UnaryKind::Negation, _) => Err(EvaluationError::MustBeNumber(1)),
-                    (UnaryKind::LogicalNot, EvaluationValue::Nil) => {
-                        Ok(EvaluationValue::Logical(true))
-                    }
-                    (UnaryKind::LogicalNot, EvaluationValue::Number(n)) => {
-                        Ok(EvaluationValue::Logical(n == 0.0))
-                    }
-                    (UnaryKind::LogicalNot, EvaluationValue::Str(_)) => todo!(),
-                    (UnaryKind::LogicalNot, EvaluationValue::Logical(l)) => {
-                        Ok(EvaluationValue::Logical(!l))
-                    }
-                }
+/// Long operator and call chains make deep trees, so children are dropped
+/// from a worklist instead of recursively.
+impl Drop for Expr {
+    fn drop(&mut self) {
+        let mut pending = vec![];
+        self.kind.take_children(&mut pending);
+        while let Some(mut expr) = pending.pop() {
+            expr.kind.take_children(&mut pending);
+        }
+    }
+}
+
+impl ExprKind {
+    /// Moves the direct subexpressions into `pending`, leaving a leaf.
+    fn take_children(&mut self, pending: &mut Vec<Expr>) {
+        match std::mem::replace(self, ExprKind::This) {
+            ExprKind::Literal(_) | ExprKind::Ident(_) | ExprKind::This | ExprKind::Super { .. } => {
             }
-            Expr::Binary { op, left, right } => {
-                let left = left.evaluate(scope)?;
-                let right = right.evaluate(scope)?;
-                match (op, left, right) {
-                    (
-                        BinaryKind::Multiplication,
-                        EvaluationValue::Number(left),
-                        EvaluationValue::Number(right),
-                    ) => Ok(EvaluationValue::Number(left * right)),
-                    (
-                        BinaryKind::Division,
-                        EvaluationValue::Number(left),
-                        EvaluationValue::Number(right),
-                    ) => Ok(EvaluationValue::Number(left / right)),
-                    (
-                        BinaryKind::Addition,
-                        EvaluationValue::Number(left),
-                        EvaluationValue::Number(right),
-                    ) => Ok(EvaluationValue::Number(left + right)),
-                    (
-                        BinaryKind::Subtraction,
-                        EvaluationValue::Number(left),
-                        EvaluationValue::Number(right),
-                    ) => Ok(EvaluationValue::Number(left - right)),
-                    (
-                        BinaryKind::Addition,
-                        EvaluationValue::Str(left),
-                        EvaluationValue::Str(right),
-                    ) => Ok(EvaluationValue::Str(left + &right)),
-
-                    (
-                        BinaryKind::Less,
-                        EvaluationValue::Number(left),
-                        EvaluationValue::Number(right),
-                    ) => Ok(EvaluationValue::Logical(left < right)),
-                    (
-                        BinaryKind::LessEqual,
-                        EvaluationValue::Number(left),
-                        EvaluationValue::Number(right),
-                    ) => Ok(EvaluationValue::Logical(left <= right)),
-                    (
-                        BinaryKind::Greater,
-                        EvaluationValue::Number(left),
-                        EvaluationValue::Number(right),
-                    ) => Ok(EvaluationValue::Logical(left > right)),
-                    (
-                        BinaryKind::GreaterEqual,
-                        EvaluationValue::Number(left),
-                        EvaluationValue::Number(right),
-                    ) => Ok(EvaluationValue::Logical(left >= right)),
-                    (
-                        BinaryKind::Equality,
-                        EvaluationValue::Number(left),
-                        EvaluationValue::Number(right),
-                    ) => Ok(EvaluationValue::Logical(left == right)),
-                    (
-                        BinaryKind::NotEquality,
-                        EvaluationValue::Number(left),
-                        EvaluationValue::Number(right),
-                    ) => Ok(EvaluationValue::Logical(left != right)),
-                    (
-                        BinaryKind::Equality,
-                        EvaluationValue::Str(left),
-                        EvaluationValue::Str(right),
-                    ) => Ok(EvaluationValue::Logical(left == right)),
-                    (
-                        BinaryKind::NotEquality,
-                        EvaluationValue::Str(left),
-                        EvaluationValue::Str(right),
-                    ) => Ok(EvaluationValue::Logical(left != right)),
-                    (
-                        BinaryKind::Equality,
-                        EvaluationValue::Logical(left),
-                        EvaluationValue::Logical(right),
-                    ) => Ok(EvaluationValue::Logical(left == right)),
-                    (
-                        BinaryKind::NotEquality,
-                        EvaluationValue::Logical(left),
-                        EvaluationValue::Logical(right),
-                    ) => Ok(EvaluationValue::Logical(left != right)),
-                    // TODO: handle specific cases, like string and number
-                    (BinaryKind::Equality, _, _) => Ok(EvaluationValue::Logical(false)),
-                    (BinaryKind::NotEquality, _, _) => Ok(EvaluationValue::Logical(false)),
-                    _ => Err(EvaluationError::OperandsMustBeNumber(1)),
-                }
+            ExprKind::Group(expr)
+            | ExprKind::Unary { operand: expr, .. }
+            | ExprKind::Assignment(_, expr)
+            | ExprKind::Get { object: expr, .. } => pending.push(*expr),
+            ExprKind::Binary { left, right, .. } | ExprKind::Logical { left, right, .. } => {
+                pending.push(*left);
+                pending.push(*right);
             }
-            Expr::Group(group) => group[0].evaluate(scope),
-            Expr::Ident(ident) => {
-                let Some(expr) = scope.get(ident) else {
-                    return Err(EvaluationError::UndefinedVariable(ident.0.clone()));
-                };
-                let expr = expr.to_owned();
-                expr.evaluate(scope)
+            ExprKind::Call { callee, args } => {
+                pending.push(*callee);
+                pending.extend(args);
             }
-            Expr::Assignment(left, right) => {
-                // global_variables.insert(left.to_owned(), *right.clone());
-                right.evaluate(scope)
+            ExprKind::Set { object, value, .. } => {
+                pending.push(*object);
+                pending.push(*value);
             }
         }
     }
 }
 
-impl std::fmt::Debug for Expr {
+impl std::fmt::Debug for ExprKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Literal(arg0) => write!(f, "{:?}", arg0),
-            Self::Group(group) => {
-                write!(f, "(group")?;
-                for expr in group {
-                    write!(f, " {:?}", expr)?;
+            Self::Group(expr) => write!(f, "(group {:?})", expr),
+            Self::Unary { op, operand, .. } => write!(f, "({:?} {:?})", op, operand),
+            Self::Binary {
+                op, left, right, ..
+            } => write!(f, "({:?} {:?} {:?})", op, left, right),
+            Self::Logical { op, left, right } => write!(f, "({:?} {:?} {:?})", op, left, right),
+            Self::Ident(ident) => write!(f, "{}", ident),
+            Self::Assignment(ident, right) => write!(f, "{} = {:?}", ident, right),
+            Self::Call { callee, args } => {
+                write!(f, "(call {:?}", callee)?;
+                for arg in args {
+                    write!(f, " {:?}", arg)?;
                 }
-                write!(f, ")")?;
-                Ok(())
+                write!(f, ")")
             }
-            Self::Unary(kind, operand) => write!(f, "({:?} {:?})", kind, operand),
-            Self::Binary { op, left, right } => write!(f, "({:?} {:?} {:?})", op, left, right),
-            Self::Ident(ident) => write!(f, "{}", ident.0),
-            Self::Assignment(ident, right) => write!(f, "{} = {:?}", ident.0, right),
+            Self::Get { object, name } => write!(f, "(get {:?} {})", object, name),
+            Self::Set {
+                object,
+                name,
+                value,
+            } => write!(f, "(set {:?} {} {:?})", object, name, value),
+            Self::This => write!(f, "this"),
+            Self::Super { method } => write!(f, "(super {})", method),
         }
     }
 }
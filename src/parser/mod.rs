@@ -1,31 +1,65 @@
-#![allow(dead_code, unused)]
-
-use std::collections::BTreeMap;
+use std::{collections::HashMap, rc::Rc};
 
 use crate::{
-    lexer::{Lexer, LexerResult, Token, TokenKind, RESERVED_WORDS},
+    lexer::{Lexer, Span, Token, TokenKind, TokenLiteral},
     parser::{
-        error::{EvaluationError, EvaluationResult, ParserError, ParserResult},
-        expr::{BinaryKind, EvaluationValue, Ident, UnaryKind},
-        stmt::Scope,
+        error::{ParserError, ParserResult},
+        expr::{BinaryKind, ExprKind, Ident, LogicalKind, UnaryKind},
+        stmt::{FunctionDecl, StmtKind},
     },
 };
 pub use expr::Expr;
 pub use literal::Literal;
 pub use stmt::Stmt;
+pub use stream::StreamParser;
 
 pub mod error;
 pub mod expr;
 pub mod literal;
 pub mod stmt;
+pub mod stream;
+
+/// Upper bound on call arguments and function parameters.
+const MAX_ARGUMENTS: usize = 255;
+
+/// How deeply parentheses, unary operators, assignments and blocks may nest.
+/// Flat chains such as `a + b + c` or `a.f().g()` do not count, however
+/// long they are.
+const MAX_NESTING: usize = 1024;
 
+/// Remaining native stack below which a pass over the tree continues on a
+/// new segment, and the size of that segment. Chains of operators and calls
+/// build arbitrarily deep trees, so every recursive pass grows its stack.
+pub(crate) const STACK_RED_ZONE: usize = 64 * 1024;
+pub(crate) const STACK_SEGMENT: usize = 1024 * 1024;
+
+/// Turns the token stream produced by [`Lexer`] into an AST.
+///
+/// Tokens are pulled from the lexer as the parser advances, so only the
+/// current token is held at any time. Iterating over a parser yields one
+/// top-level statement at a time.
+///
+/// The lexer borrows the complete source text. To parse input as it is
+/// read, without holding all of it, use [`StreamParser`].
+///
+/// The parser never evaluates anything: running the produced statements is
+/// the job of [`crate::interpreter::Interpreter`].
 #[derive(Debug)]
-pub struct Parser {
-    tokens: Vec<Token>,
-    cursor: usize,
-    current_precedence: u8,
-    pub result: ParserResult<()>,
-    pub global_scope: Scope,
+pub struct Parser<'src> {
+    lexer: Lexer<'src>,
+    /// The next token to be consumed.
+    current: Token<'src>,
+    previous_kind: TokenKind,
+    previous_span: Span,
+    /// Tokens consumed so far, used to make sure error recovery progresses.
+    consumed: usize,
+    errors: Vec<ParserError>,
+    /// One [`Ident`] per distinct name, so equal names in the tree share it.
+    names: HashMap<&'src str, Ident>,
+    /// Whether the last statement may leave out its `;`, as in the REPL.
+    optional_final_semicolon: bool,
+    /// Nesting of the node being parsed, see [`MAX_NESTING`].
+    depth: usize,
 }
 
 fn is_binary_op(kind: TokenKind) -> bool {
@@ -41,334 +75,685 @@ fn is_binary_op(kind: TokenKind) -> bool {
             | TokenKind::GreaterEqual
             | TokenKind::EqualEqual
             | TokenKind::BangEqual
+            | TokenKind::AND
+            | TokenKind::OR
     )
 }
 
-impl Parser {
-    pub fn new(mut lexer: Lexer) -> Self {
-        let tokens = lexer.parse_to_end();
+impl<'src> Parser<'src> {
+    pub fn new(mut lexer: Lexer<'src>) -> Self {
+        let current = lexer.next().unwrap_or(Token::EOF);
         Self {
-            tokens,
-            cursor: 0,
-            current_precedence: 0,
-            global_scope: BTreeMap::new(),
-            result: lexer_to_parser_result(lexer.result),
+            lexer,
+            current,
+            previous_kind: TokenKind::Eof,
+            previous_span: Span::default(),
+            consumed: 0,
+            errors: vec![],
+            names: HashMap::new(),
+            optional_final_semicolon: false,
+            depth: 0,
         }
     }
-    fn peek_token(&self) -> Option<Token> {
-        // TODO: maybe return reference
-        self.tokens[self.cursor..].iter().next().cloned()
-    }
 
-    fn advance(&mut self) {
-        self.cursor += 1;
+    /// Accepts a final statement without its `;` when the input ends there,
+    /// so an entry like `1 + 2` parses as an expression statement.
+    pub fn with_optional_final_semicolon(mut self, optional: bool) -> Self {
+        self.optional_final_semicolon = optional;
+        self
     }
 
-    fn get_precedence(&self, kind: &TokenKind) -> u8 {
-        match kind {
-            TokenKind::Star | TokenKind::Slash => 10,
-            TokenKind::Plus | TokenKind::Minus | TokenKind::Less => 5,
-            TokenKind::LessEqual
-            | TokenKind::Greater
-            | TokenKind::GreaterEqual
-            | TokenKind::EqualEqual
-            | TokenKind::BangEqual => 2,
-            _ => 0,
+    /// Parses the whole token stream as a program.
+    ///
+    /// Parsing carries on past syntax errors, so on failure every lexer and
+    /// parser error in the source is returned, in that order.
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Vec<ParserError>> {
+        let mut stmts = vec![];
+        while !self.is_at_end() {
+            if let Some(stmt) = self.parse_declaration() {
+                stmts.push(stmt);
+            }
         }
+        self.finish(stmts)
     }
 
-    fn expect_expression_err(&mut self) -> Option<Stmt> {
-        self.result = Err(ParserError::ExpectedExpression {
-            line: 1,
-            lexeme: "print".to_string(),
+    /// Parses a single expression, as used by the `parse` and `evaluate`
+    /// commands. Anything after the expression is an error.
+    pub fn parse_expression(&mut self) -> Result<Expr, Vec<ParserError>> {
+        let expr = self.parse_assignment().and_then(|expr| {
+            self.consume(TokenKind::Eof, "Expect end of expression.")?;
+            Ok(expr)
         });
-        // self.result = Err(ParserError::UndefinedVariable("test".to_string()));
-        None
+        match expr {
+            Ok(expr) => self.finish(expr),
+            Err(err) => {
+                self.errors.push(err);
+                self.finish(Expr::nil(self.previous_span()))
+            }
+        }
     }
 
-    fn expect_print_stmt(&mut self) -> Option<Stmt> {
-        let expr = self.parse_expression(0);
-        match expr {
-            Some(expr) => Some(Stmt::Print(expr)),
-            None => self.expect_expression_err(),
+    fn finish<T>(&mut self, parsed: T) -> Result<T, Vec<ParserError>> {
+        let errors = self.take_errors(usize::MAX);
+        if errors.is_empty() {
+            Ok(parsed)
+        } else {
+            Err(errors)
         }
     }
 
-    fn expect_assignment_stmt(
-        &mut self,
-        ident: Ident,
-        scope: Option<&mut Scope>,
-        outer_scope: &mut Option<&mut Scope>,
-        is_decl: bool,
-    ) -> Option<Stmt> {
-        match self.parse_expression(0) {
-            Some(expr) => {
-                let ass_expr = expr.evaluate(&self.global_scope);
-                if let Err(EvaluationError::UndefinedVariable(var)) = ass_expr {
-                    self.result = Err(ParserError::UndefinedVariable(var));
-                    return None;
-                }
-                let ass_expr = ass_expr.ok()?.to_expr();
-                if is_decl {
-                    if let Some(scope) = scope {
-                        scope.insert(ident.to_owned(), ass_expr.clone());
-                    } else {
-                        self.global_scope.insert(ident.to_owned(), ass_expr.clone());
-                    }
-                } else if let Some(scope) = scope {
-                    match scope.entry(ident.clone()) {
-                        std::collections::btree_map::Entry::Vacant(_) => {
-                            if let Some(outer_scope) = outer_scope {
-                                outer_scope.insert(ident.clone(), ass_expr.clone());
-                            }
-                        }
-                        std::collections::btree_map::Entry::Occupied(mut occupied_entry) => {
-                            occupied_entry.insert(ass_expr.clone());
-                        }
-                    }
+    /// Lexer errors before byte offset `end` followed by all parser errors.
+    /// Lexer errors from reading ahead past `end` are kept for later.
+    fn take_errors(&mut self, end: usize) -> Vec<ParserError> {
+        let (later, earlier): (Vec<_>, Vec<_>) = std::mem::take(&mut self.lexer.errors)
+            .into_iter()
+            .partition(|err| err.span().is_some_and(|span| span.start >= end));
+        self.lexer.errors = later;
+        let mut errors: Vec<ParserError> =
+            earlier.into_iter().map(ParserError::LexerError).collect();
+        errors.append(&mut self.errors);
+        errors
+    }
+
+    /// Parses one statement, recording the error and skipping to the next
+    /// statement boundary when it is malformed.
+    fn parse_declaration(&mut self) -> Option<Stmt> {
+        let start = self.consumed;
+        match self.parse_statement() {
+            Ok(stmt) => Some(stmt),
+            Err(err) => {
+                self.errors.push(err);
+                self.synchronize();
+                if self.consumed == start {
+                    self.advance();
                 }
-                Some(Stmt::Declaration(ident, ass_expr))
-            }
-            None => {
-                self.result = Err(ParserError::UndefinedVariable("test".to_string()));
                 None
             }
         }
     }
 
-    fn parse_ident(&mut self) -> Option<Ident> {
-        let next_token = self.peek_token()?;
-        self.advance();
-        match next_token.kind {
-            TokenKind::Identifier => Some(Ident(next_token.lexeme.to_string())),
-            _ => None,
+    /// Discards tokens until the end of the current statement or the start
+    /// of the next one.
+    fn synchronize(&mut self) {
+        while !self.is_at_end() {
+            if self.consumed > 0 && self.previous_kind == TokenKind::Semicolon {
+                return;
+            }
+            match self.peek_token().kind {
+                TokenKind::CLASS
+                | TokenKind::FUN
+                | TokenKind::VAR
+                | TokenKind::FOR
+                | TokenKind::IF
+                | TokenKind::WHILE
+                | TokenKind::PRINT
+                | TokenKind::RETURN => return,
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    fn peek_token(&self) -> &Token<'src> {
+        &self.current
+    }
+
+    /// Consumes the current token and reads the next one from the lexer.
+    /// The end of input is never consumed.
+    fn advance(&mut self) -> Token<'src> {
+        if self.is_at_end() {
+            return self.current.clone();
+        }
+        let next = self.lexer.next().unwrap_or(Token::EOF);
+        let token = std::mem::replace(&mut self.current, next);
+        self.previous_kind = token.kind;
+        self.previous_span = token.span;
+        self.consumed += 1;
+        token
+    }
+
+    /// Span of the most recently consumed token.
+    fn previous_span(&self) -> Span {
+        self.previous_span
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.peek_token().kind == TokenKind::Eof
+    }
+
+    fn check(&self, kind: TokenKind) -> bool {
+        self.peek_token().kind == kind
+    }
+
+    fn consume(&mut self, kind: TokenKind, message: &'static str) -> ParserResult<Token<'src>> {
+        if self.check(kind) {
+            return Ok(self.advance());
+        }
+        if kind == TokenKind::Semicolon && self.optional_final_semicolon && self.is_at_end() {
+            return Ok(self.current.clone());
+        }
+        let token = self.peek_token();
+        Err(ParserError::Expected {
+            span: token.span,
+            lexeme: token.lexeme.to_string(),
+            message,
+        })
+    }
+
+    /// Runs `parse` one level deeper in the tree.
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> ParserResult<T>) -> ParserResult<T> {
+        let depth = self.depth;
+        let result = self
+            .deepen()
+            .and_then(|()| stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || parse(self)));
+        self.depth = depth;
+        result
+    }
+
+    /// Accounts for one more level of nesting, failing past [`MAX_NESTING`].
+    /// The enclosing [`Parser::nested`] call restores the depth.
+    fn deepen(&mut self) -> ParserResult<()> {
+        self.depth += 1;
+        if self.depth <= MAX_NESTING {
+            return Ok(());
+        }
+        let token = self.peek_token();
+        Err(ParserError::TooDeeplyNested {
+            span: token.span,
+            lexeme: token.lexeme.to_string(),
+        })
+    }
+
+    fn get_precedence(&self, kind: &TokenKind) -> u8 {
+        match kind {
+            TokenKind::Star | TokenKind::Slash => 10,
+            TokenKind::Plus | TokenKind::Minus => 5,
+            TokenKind::Less
+            | TokenKind::LessEqual
+            | TokenKind::Greater
+            | TokenKind::GreaterEqual => 4,
+            TokenKind::EqualEqual | TokenKind::BangEqual => 3,
+            TokenKind::AND => 2,
+            TokenKind::OR => 1,
+            _ => 0,
+        }
+    }
+
+    fn expected_expression(&self, token: &Token) -> ParserError {
+        ParserError::ExpectedExpression {
+            span: token.span,
+            lexeme: token.lexeme.to_string(),
         }
     }
 
-    pub fn parse_statement(
-        &mut self,
-        scope: Option<&mut Scope>,
-        outer_scope: &mut Option<&mut Scope>,
-    ) -> Option<Stmt> {
-        let token = self.peek_token()?;
-        let stmt = match token.kind {
+    fn ident(&mut self, name: &'src str) -> Ident {
+        self.names
+            .entry(name)
+            .or_insert_with(|| Ident::new(name))
+            .clone()
+    }
+
+    fn parse_ident(&mut self) -> ParserResult<Ident> {
+        let token = self.consume(TokenKind::Identifier, "Expect variable name.")?;
+        Ok(self.ident(token.lexeme))
+    }
+
+    pub fn parse_statement(&mut self) -> ParserResult<Stmt> {
+        self.nested(Self::parse_statement_kind)
+    }
+
+    fn parse_statement_kind(&mut self) -> ParserResult<Stmt> {
+        let start = self.peek_token().span;
+        let kind = match self.peek_token().kind {
             TokenKind::PRINT => {
                 self.advance();
-                self.expect_print_stmt()
+                let expr = self.parse_assignment()?;
+                self.consume(TokenKind::Semicolon, "Expect ';' after value.")?;
+                StmtKind::Print(expr)
             }
-            TokenKind::VAR => {
+            TokenKind::VAR => return self.parse_var_declaration(),
+            TokenKind::LeftBrace => {
                 self.advance();
-                let ident = self.parse_ident()?;
-                match self.peek_token()?.kind {
-                    TokenKind::Equal => {
-                        self.advance();
-                        self.expect_assignment_stmt(ident, scope, outer_scope, true)
-                    }
-                    TokenKind::Semicolon => {
-                        if let Some(scope) = scope {
-                            scope.insert(ident.clone(), Expr::NIL);
-                        } else {
-                            self.global_scope.insert(ident.clone(), Expr::NIL);
-                        }
-                        Some(Stmt::Declaration(ident, Expr::NIL))
+                let mut stmts = vec![];
+                while !self.check(TokenKind::RightBrace) && !self.is_at_end() {
+                    if let Some(stmt) = self.parse_declaration() {
+                        stmts.push(stmt);
                     }
-                    _ => unreachable!("Handle errors"),
                 }
+                self.consume(TokenKind::RightBrace, "Expect '}' after block.")?;
+                StmtKind::Block(stmts)
             }
-            TokenKind::Identifier => {
-                let ident = self.parse_ident()?;
-                match self.peek_token()?.kind {
-                    TokenKind::Equal => {
-                        self.advance();
-                        self.expect_assignment_stmt(ident, scope, &mut None, false)
-                        // self.global_scope.insert(ident.to_owned(), ass_expr);
-                    }
-                    t => {
-                        self.result = Err(ParserError::UndefinedVariable("test".to_string()));
-                        None
-                    }
+            TokenKind::IF => {
+                self.advance();
+                self.consume(TokenKind::LeftParen, "Expect '(' after 'if'.")?;
+                let condition = self.parse_assignment()?;
+                self.consume(TokenKind::RightParen, "Expect ')' after if condition.")?;
+                let then_branch = Box::new(self.parse_body()?);
+                // An `else` binds to the nearest `if`.
+                let else_branch = if self.check(TokenKind::ELSE) {
+                    self.advance();
+                    Some(Box::new(self.parse_body()?))
+                } else {
+                    None
+                };
+                StmtKind::If {
+                    condition,
+                    then_branch,
+                    else_branch,
                 }
             }
-            TokenKind::LeftBrace => {
+            TokenKind::WHILE => {
                 self.advance();
-                let mut stmts = vec![];
-                let mut inner_scope = match scope {
-                    Some(outer_scope) => outer_scope.clone(),
-                    None => self.global_scope.clone(),
+                self.consume(TokenKind::LeftParen, "Expect '(' after 'while'.")?;
+                let condition = self.parse_assignment()?;
+                self.consume(TokenKind::RightParen, "Expect ')' after condition.")?;
+                let body = Box::new(self.parse_body()?);
+                StmtKind::While { condition, body }
+            }
+            TokenKind::FOR => return self.parse_for_statement(),
+            TokenKind::FUN => {
+                self.advance();
+                StmtKind::Function(Rc::new(self.parse_function(start)?))
+            }
+            TokenKind::CLASS => {
+                self.advance();
+                let name = self.consume(TokenKind::Identifier, "Expect class name.")?;
+                let superclass = if self.check(TokenKind::Less) {
+                    self.advance();
+                    let superclass =
+                        self.consume(TokenKind::Identifier, "Expect superclass name.")?;
+                    Some(Expr::new(
+                        ExprKind::Ident(self.ident(superclass.lexeme)),
+                        superclass.span,
+                    ))
+                } else {
+                    None
                 };
-                loop {
-                    if let Some(TokenKind::RightBrace) = self.peek_token().map(|t| t.kind) {
-                        self.advance();
-                        break;
-                    }
-                    let Some(stmt) = self.parse_statement(Some(&mut inner_scope), outer_scope)
-                    else {
-                        self.result = Err(ParserError::UnmatchedParens(1));
-                        return None;
-                    };
-                    stmts.push(stmt);
+                self.consume(TokenKind::LeftBrace, "Expect '{' before class body.")?;
+                let mut methods = vec![];
+                while !self.check(TokenKind::RightBrace) && !self.is_at_end() {
+                    let start = self.peek_token().span;
+                    methods.push(Rc::new(self.parse_function(start)?));
+                }
+                self.consume(TokenKind::RightBrace, "Expect '}' after class body.")?;
+                StmtKind::Class {
+                    name: self.ident(name.lexeme),
+                    superclass,
+                    methods,
                 }
-                Some(Stmt::Block(stmts, inner_scope))
             }
-            _ => Some(Stmt::Expr(self.parse_expression(0)?)),
+            TokenKind::RETURN => {
+                self.advance();
+                let value = if self.check(TokenKind::Semicolon) {
+                    None
+                } else {
+                    Some(self.parse_assignment()?)
+                };
+                self.consume(TokenKind::Semicolon, "Expect ';' after return value.")?;
+                StmtKind::Return(value)
+            }
+            _ => return self.parse_expression_statement(),
         };
+        Ok(Stmt::new(kind, start.to(self.previous_span())))
+    }
 
-        if stmt.is_some() {
-            let token = self.peek_token()?;
-            if matches!(token.kind, TokenKind::Semicolon) {
+    /// Parses the body of an `if`, `else`, `while` or `for`, which may be any
+    /// statement except a declaration.
+    fn parse_body(&mut self) -> ParserResult<Stmt> {
+        match self.peek_token().kind {
+            TokenKind::VAR | TokenKind::FUN | TokenKind::CLASS => {
+                Err(self.expected_expression(self.peek_token()))
+            }
+            _ => self.parse_statement(),
+        }
+    }
+
+    /// Parses the part of a function declaration after `fun`.
+    fn parse_function(&mut self, start: Span) -> ParserResult<FunctionDecl> {
+        let name = self.parse_ident()?;
+        self.consume(TokenKind::LeftParen, "Expect '(' after function name.")?;
+        let mut params = vec![];
+        if !self.check(TokenKind::RightParen) {
+            loop {
+                if params.len() >= MAX_ARGUMENTS {
+                    self.too_many("Can't have more than 255 parameters.");
+                }
+                params.push(self.parse_ident()?);
+                if !self.check(TokenKind::Comma) {
+                    break;
+                }
                 self.advance();
             }
         }
-        stmt
+        self.consume(TokenKind::RightParen, "Expect ')' after parameters.")?;
+
+        self.consume(TokenKind::LeftBrace, "Expect '{' before function body.")?;
+        let mut body = vec![];
+        while !self.check(TokenKind::RightBrace) && !self.is_at_end() {
+            if let Some(stmt) = self.parse_declaration() {
+                body.push(stmt);
+            }
+        }
+        self.consume(TokenKind::RightBrace, "Expect '}' after block.")?;
+
+        Ok(FunctionDecl {
+            name,
+            params,
+            body,
+            span: start.to(self.previous_span()),
+        })
     }
 
-    fn parse_expression(&mut self, precedence: u8) -> Option<Expr> {
-        let mut primary = self.parse_primary()?;
+    /// Records a limit violation without aborting the current statement.
+    fn too_many(&mut self, message: &'static str) {
+        let token = self.peek_token();
+        self.errors.push(ParserError::Expected {
+            span: token.span,
+            lexeme: token.lexeme.to_string(),
+            message,
+        });
+    }
 
-        let next_token = self.peek_token()?;
-        if is_binary_op(next_token.kind) {
-            while let Some(next_token) = self.peek_token() {
-                let op_precedence = self.get_precedence(&next_token.kind);
+    fn parse_var_declaration(&mut self) -> ParserResult<Stmt> {
+        let start = self.advance().span;
+        let ident = self.parse_ident()?;
+        let initializer = if self.check(TokenKind::Equal) {
+            self.advance();
+            self.parse_assignment()?
+        } else {
+            Expr::nil(self.peek_token().span)
+        };
+        self.consume(
+            TokenKind::Semicolon,
+            "Expect ';' after variable declaration.",
+        )?;
+        Ok(Stmt::new(
+            StmtKind::Declaration(ident, initializer),
+            start.to(self.previous_span()),
+        ))
+    }
 
-                if op_precedence <= precedence {
-                    break;
-                }
+    fn parse_expression_statement(&mut self) -> ParserResult<Stmt> {
+        let expr = self.parse_assignment()?;
+        self.consume(TokenKind::Semicolon, "Expect ';' after expression.")?;
+        let span = expr.span.to(self.previous_span());
+        Ok(Stmt::new(StmtKind::Expr(expr), span))
+    }
 
+    /// Parses a `for` loop, desugaring it into a `while` loop wrapped in
+    /// blocks for the initializer and increment clauses.
+    fn parse_for_statement(&mut self) -> ParserResult<Stmt> {
+        let start = self.advance().span;
+        self.consume(TokenKind::LeftParen, "Expect '(' after 'for'.")?;
+
+        let initializer = match self.peek_token().kind {
+            TokenKind::Semicolon => {
                 self.advance();
-                let operator = match next_token.kind {
-                    TokenKind::Plus => BinaryKind::Addition,
-                    TokenKind::Minus => BinaryKind::Subtraction,
-                    TokenKind::Star => BinaryKind::Multiplication,
-                    TokenKind::Slash => BinaryKind::Division,
-                    TokenKind::Less => BinaryKind::Less,
-                    TokenKind::LessEqual => BinaryKind::LessEqual,
-                    TokenKind::Greater => BinaryKind::Greater,
-                    TokenKind::GreaterEqual => BinaryKind::GreaterEqual,
-                    TokenKind::EqualEqual => BinaryKind::Equality,
-                    TokenKind::BangEqual => BinaryKind::NotEquality,
-                    _ => unreachable!(),
-                };
+                None
+            }
+            TokenKind::VAR => Some(self.parse_var_declaration()?),
+            _ => Some(self.parse_expression_statement()?),
+        };
 
-                let right = self.parse_expression(op_precedence);
-                if right.is_none() {
-                    let lexeme = &self.tokens[self.cursor - 1].lexeme;
-                    eprintln!("[line 1] Error at '{}': Expect expression.", lexeme);
-                    self.result = Err(ParserError::ExpectedExpression {
-                        line: 1,
-                        lexeme: lexeme.to_string(),
-                    });
-                    return None;
-                }
+        let condition = if self.check(TokenKind::Semicolon) {
+            Expr::new(
+                ExprKind::Literal(Literal::Logical(true)),
+                self.peek_token().span,
+            )
+        } else {
+            self.parse_assignment()?
+        };
+        self.consume(TokenKind::Semicolon, "Expect ';' after loop condition.")?;
 
-                primary = Expr::Binary {
-                    op: operator,
-                    left: Box::new(primary),
-                    right: Box::new(right?),
-                };
-            }
+        let increment = if self.check(TokenKind::RightParen) {
+            None
+        } else {
+            Some(self.parse_assignment()?)
+        };
+        self.consume(TokenKind::RightParen, "Expect ')' after for clauses.")?;
+
+        let mut body = self.parse_body()?;
+        let span = start.to(self.previous_span());
+
+        if let Some(increment) = increment {
+            let increment_span = increment.span;
+            let increment = Stmt::new(StmtKind::Expr(increment), increment_span);
+            body = Stmt::new(StmtKind::Block(vec![body, increment]), span);
+        }
+        body = Stmt::new(
+            StmtKind::While {
+                condition,
+                body: Box::new(body),
+            },
+            span,
+        );
+        if let Some(initializer) = initializer {
+            body = Stmt::new(StmtKind::Block(vec![initializer, body]), span);
         }
-        if self.result.is_err() {
-            return None;
+        Ok(body)
+    }
+
+    fn parse_assignment(&mut self) -> ParserResult<Expr> {
+        let mut expr = self.parse_binary(0)?;
+
+        if self.check(TokenKind::Equal) {
+            let equal = self.advance();
+            let value = self.nested(Self::parse_assignment)?;
+            let span = expr.span.to(value.span);
+            return match std::mem::replace(&mut expr.kind, ExprKind::This) {
+                ExprKind::Ident(ident) => Ok(Expr::new(
+                    ExprKind::Assignment(ident, Box::new(value)),
+                    span,
+                )),
+                ExprKind::Get { object, name } => Ok(Expr::new(
+                    ExprKind::Set {
+                        object,
+                        name,
+                        value: Box::new(value),
+                    },
+                    span,
+                )),
+                _ => Err(ParserError::InvalidAssignmentTarget(equal.span)),
+            };
         }
 
-        Some(primary)
+        Ok(expr)
     }
 
-    fn parse_primary(&mut self) -> Option<Expr> {
-        let token = self.peek_token()?;
-        self.advance();
+    fn parse_binary(&mut self, precedence: u8) -> ParserResult<Expr> {
+        self.nested(|parser| parser.parse_binary_operands(precedence))
+    }
+
+    fn parse_binary_operands(&mut self, precedence: u8) -> ParserResult<Expr> {
+        let mut primary = self.parse_call()?;
+
+        while is_binary_op(self.peek_token().kind) {
+            let next_token = self.peek_token().clone();
+            let op_precedence = self.get_precedence(&next_token.kind);
 
-        match token.kind {
-            TokenKind::NIL => Some(Expr::NIL),
-            TokenKind::TRUE => Some(Expr::Literal(Literal::Logical(true))),
-            TokenKind::FALSE => Some(Expr::Literal(Literal::Logical(false))),
-            TokenKind::NumberLiteral => {
-                Some(Expr::Literal(Literal::Number(token.literal?.parse().ok()?)))
+            if op_precedence <= precedence {
+                break;
             }
-            TokenKind::StringLiteral => Some(Expr::Literal(Literal::Str(token.literal?))),
-            TokenKind::LeftParen => {
-                if let Some(pos) = self.tokens[self.cursor..]
-                    .iter()
-                    .position(|tk| tk.kind == TokenKind::RightParen)
-                {
-                    let mut group_tokens = vec![];
-                    for expr in self.by_ref() {
-                        // TODO: handle statement cases
-                        if let Stmt::Expr(expr) = expr {
-                            group_tokens.push(expr);
-                        }
+
+            self.advance();
+            let right = Box::new(self.parse_binary(op_precedence)?);
+            let span = primary.span.to(right.span);
+            let left = Box::new(primary);
+            let kind = match next_token.kind {
+                TokenKind::AND => ExprKind::Logical {
+                    op: LogicalKind::And,
+                    left,
+                    right,
+                },
+                TokenKind::OR => ExprKind::Logical {
+                    op: LogicalKind::Or,
+                    left,
+                    right,
+                },
+                kind => {
+                    let op = match kind {
+                        TokenKind::Plus => BinaryKind::Addition,
+                        TokenKind::Minus => BinaryKind::Subtraction,
+                        TokenKind::Star => BinaryKind::Multiplication,
+                        TokenKind::Slash => BinaryKind::Division,
+                        TokenKind::Less => BinaryKind::Less,
+                        TokenKind::LessEqual => BinaryKind::LessEqual,
+                        TokenKind::Greater => BinaryKind::Greater,
+                        TokenKind::GreaterEqual => BinaryKind::GreaterEqual,
+                        TokenKind::EqualEqual => BinaryKind::Equality,
+                        TokenKind::BangEqual => BinaryKind::NotEquality,
+                        _ => unreachable!(),
+                    };
+                    ExprKind::Binary {
+                        op,
+                        op_span: next_token.span,
+                        left,
+                        right,
                     }
-                    let group = Expr::Group(group_tokens);
-                    Some(group)
-                } else {
-                    eprintln!("[line 1] Error: Unmatched parens");
-                    self.result = Err(ParserError::UnmatchedParens(1));
-                    None
                 }
+            };
+            primary = Expr::new(kind, span);
+        }
+
+        Ok(primary)
+    }
+
+    fn parse_call(&mut self) -> ParserResult<Expr> {
+        let mut expr = self.parse_primary()?;
+
+        loop {
+            match self.peek_token().kind {
+                TokenKind::LeftParen => {
+                    self.advance();
+                    let args = self.parse_arguments()?;
+                    let span = expr.span.to(self.previous_span());
+                    expr = Expr::new(
+                        ExprKind::Call {
+                            callee: Box::new(expr),
+                            args,
+                        },
+                        span,
+                    );
+                }
+                TokenKind::Dot => {
+                    self.advance();
+                    let name =
+                        self.consume(TokenKind::Identifier, "Expect property name after '.'.")?;
+                    let span = expr.span.to(name.span);
+                    expr = Expr::new(
+                        ExprKind::Get {
+                            object: Box::new(expr),
+                            name: self.ident(name.lexeme),
+                        },
+                        span,
+                    );
+                }
+                _ => break,
             }
-            TokenKind::Bang => {
-                let operand = self.parse_expression(10)?;
-                Some(Expr::Unary(UnaryKind::LogicalNot, Box::new(operand)))
-            }
-            TokenKind::Minus => {
-                let operand = self.parse_expression(10)?;
-                Some(Expr::Unary(UnaryKind::Negation, Box::new(operand)))
-            }
-            TokenKind::Eof => None,
-            TokenKind::RightParen => None,
-            TokenKind::Identifier if self.peek_token()?.kind == TokenKind::Equal => {
-                let ident = Ident(token.lexeme.to_string());
-                self.advance(); // Equal
-                let expr = self.parse_expression(0)?;
-
-                let ass_expr = expr.evaluate(&self.global_scope);
-                if let Err(EvaluationError::UndefinedVariable(var)) = ass_expr {
-                    self.result = Err(ParserError::UndefinedVariable(var));
-                    return None;
+        }
+
+        Ok(expr)
+    }
+
+    /// Parses call arguments after the opening parenthesis.
+    fn parse_arguments(&mut self) -> ParserResult<Vec<Expr>> {
+        let mut args = vec![];
+        if !self.check(TokenKind::RightParen) {
+            loop {
+                if args.len() >= MAX_ARGUMENTS {
+                    self.too_many("Can't have more than 255 arguments.");
                 }
-                let ass_expr = ass_expr.ok()?.to_expr();
-                // self.global_scope.insert(ident.to_owned(), ass_expr);
-                Some(Expr::Assignment(ident, Box::new(expr)))
+                args.push(self.parse_assignment()?);
+                if !self.check(TokenKind::Comma) {
+                    break;
+                }
+                self.advance();
             }
-            TokenKind::Identifier => Some(Expr::Ident(Ident(token.lexeme.to_string()))),
-            t => {
-                dbg!(t);
-                eprintln!("[line 1] Error: Unexpected token");
-                self.result = Err(ParserError::UnexpectedToken(1));
-                None
-            } // t => unimplemented!("{:?}", t),
         }
+        self.consume(TokenKind::RightParen, "Expect ')' after arguments.")?;
+        Ok(args)
     }
 
-    pub fn run(&mut self, stmt: Stmt) -> EvaluationResult<EvaluationValue> {
-        match stmt {
-            Stmt::Expr(expr) => {
-                let evaluation_result = expr.evaluate(&self.global_scope)?;
-                Ok(EvaluationValue::Void)
+    fn parse_primary(&mut self) -> ParserResult<Expr> {
+        let token = self.advance();
+
+        let kind = match token.kind {
+            TokenKind::NIL => ExprKind::Literal(Literal::Nil),
+            TokenKind::TRUE => ExprKind::Literal(Literal::Logical(true)),
+            TokenKind::FALSE => ExprKind::Literal(Literal::Logical(false)),
+            TokenKind::NumberLiteral => match token.literal {
+                Some(TokenLiteral::Number(number)) => ExprKind::Literal(Literal::Number(number)),
+                _ => return Err(self.expected_expression(&token)),
+            },
+            TokenKind::StringLiteral => match token.literal {
+                Some(TokenLiteral::Str(value)) => {
+                    ExprKind::Literal(Literal::Str(value.into_owned()))
+                }
+                _ => return Err(self.expected_expression(&token)),
+            },
+            TokenKind::LeftParen => {
+                let expr = self.parse_assignment()?;
+                if !self.check(TokenKind::RightParen) {
+                    return Err(ParserError::UnmatchedParens(token.span));
+                }
+                self.advance();
+                ExprKind::Group(Box::new(expr))
             }
-            Stmt::Print(expr) => {
-                println!("{:?}", expr.evaluate(&self.global_scope)?);
-                Ok(EvaluationValue::Void)
+            TokenKind::Bang => {
+                let operand = self.parse_binary(10)?;
+                ExprKind::Unary {
+                    op: UnaryKind::LogicalNot,
+                    op_span: token.span,
+                    operand: Box::new(operand),
+                }
             }
-            Stmt::Declaration(_left, _right) => Ok(EvaluationValue::Void),
-            Stmt::Block(block, ref scope) => {
-                for stmt in block {
-                    stmt.run(scope)?;
+            TokenKind::Minus => {
+                let operand = self.parse_binary(10)?;
+                ExprKind::Unary {
+                    op: UnaryKind::Negation,
+                    op_span: token.span,
+                    operand: Box::new(operand),
                 }
-                Ok(EvaluationValue::Void)
-            } // Stmt::Assignment(_left, _right) => Ok(EvaluationValue::Void),
-        }
+            }
+            TokenKind::Identifier => ExprKind::Ident(self.ident(token.lexeme)),
+            TokenKind::THIS => ExprKind::This,
+            TokenKind::SUPER => {
+                self.consume(TokenKind::Dot, "Expect '.' after 'super'.")?;
+                let method =
+                    self.consume(TokenKind::Identifier, "Expect superclass method name.")?;
+                ExprKind::Super {
+                    method: self.ident(method.lexeme),
+                }
+            }
+            _ => return Err(self.expected_expression(&token)),
+        };
+        Ok(Expr::new(kind, token.span.to(self.previous_span())))
     }
 }
 
-fn lexer_to_parser_result(lexer_result: LexerResult<()>) -> ParserResult<()> {
-    Ok(lexer_result?)
-}
-
-impl Iterator for Parser {
-    type Item = Stmt;
+/// Parses one top-level statement per item, reading only as far into the
+/// source as that statement goes. A malformed statement is yielded as the
+/// errors found while reading it, and parsing resumes after it.
+impl Iterator for Parser<'_> {
+    type Item = Result<Stmt, Vec<ParserError>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.parse_statement(None, &mut None)
+        if self.is_at_end() {
+            let errors = self.take_errors(usize::MAX);
+            return (!errors.is_empty()).then_some(Err(errors));
+        }
+        // A statement that parsed owns the lexer errors up to its last
+        // token; one that failed also owns those up to where recovery stopped.
+        let stmt = self.parse_declaration();
+        let end = match stmt {
+            Some(_) => self.previous_span.end,
+            None => self.current.span.start,
+        };
+        let errors = self.take_errors(end);
+        match stmt {
+            Some(stmt) if errors.is_empty() => Some(Ok(stmt)),
+            _ => Some(Err(errors)),
+        }
     }
 }
@@ -1,58 +1,113 @@
-use std::collections::BTreeMap;
+use std::rc::Rc;
 
 use crate::{
-    lexer::Token,
-    parser::{
-        error::EvaluationResult,
-        expr::{EvaluationValue, Ident},
-        Expr,
-    },
+    lexer::Span,
+    parser::{expr::Ident, Expr},
 };
 
-pub type Scope = BTreeMap<Ident, Expr>;
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+impl Stmt {
+    pub fn new(kind: StmtKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
 
-pub enum Stmt {
+pub enum StmtKind {
     Expr(Expr),
     Print(Expr),
     Declaration(Ident, Expr),
-    Block(Vec<Stmt>, Scope),
+    Block(Vec<Stmt>),
+    If {
+        condition: Expr,
+        then_branch: Box<Stmt>,
+        else_branch: Option<Box<Stmt>>,
+    },
+    While {
+        condition: Expr,
+        body: Box<Stmt>,
+    },
+    Function(Rc<FunctionDecl>),
+    /// `None` for a bare `return;`.
+    Return(Option<Expr>),
+    Class {
+        name: Ident,
+        superclass: Option<Expr>,
+        methods: Vec<Rc<FunctionDecl>>,
+    },
 }
 
-impl Stmt {
-    pub fn run(&self, scope: &Scope) -> EvaluationResult<EvaluationValue> {
-        match self {
-            Stmt::Expr(expr) => {
-                let evaluation_result = expr.evaluate(scope)?;
-                Ok(EvaluationValue::Void)
-            }
-            Stmt::Print(expr) => {
-                println!("{:?}", expr.evaluate(scope)?);
-                Ok(EvaluationValue::Void)
-            }
-            Stmt::Declaration(_left, _right) => Ok(EvaluationValue::Void),
-            Stmt::Block(block, scope) => {
-                for stmt in block {
-                    stmt.run(scope);
-                }
-                Ok(EvaluationValue::Void)
-            } // Stmt::Assignment(_left, _right) => Ok(EvaluationValue::Void),
-        }
-    }
+/// A `fun` declaration, shared between the AST and the closures created from it.
+pub struct FunctionDecl {
+    pub name: Ident,
+    pub params: Vec<Ident>,
+    pub body: Vec<Stmt>,
+    pub span: Span,
 }
 
 impl std::fmt::Debug for Stmt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl std::fmt::Debug for StmtKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Stmt::Expr(expr) => expr.fmt(f),
-            Stmt::Print(expr) => write!(f, "print {:?};", expr),
-            Stmt::Declaration(left, right) => write!(f, "var {:?} = {:?};", left.0, right),
-            Stmt::Block(block, _) => {
+            StmtKind::Expr(expr) => expr.fmt(f),
+            StmtKind::Print(expr) => write!(f, "print {:?};", expr),
+            StmtKind::Declaration(left, right) => write!(f, "var {:?} = {:?};", left, right),
+            StmtKind::Block(block) => {
                 writeln!(f, "{{")?;
                 for stmt in block {
                     stmt.fmt(f)?;
                 }
                 writeln!(f, "}}")
             }
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                write!(f, "if ({:?}) {:?}", condition, then_branch)?;
+                match else_branch {
+                    Some(else_branch) => write!(f, " else {:?}", else_branch),
+                    None => Ok(()),
+                }
+            }
+            StmtKind::While { condition, body } => write!(f, "while ({:?}) {:?}", condition, body),
+            StmtKind::Function(decl) => decl.fmt(f),
+            StmtKind::Return(Some(expr)) => write!(f, "return {:?};", expr),
+            StmtKind::Return(None) => write!(f, "return;"),
+            StmtKind::Class {
+                name,
+                superclass,
+                methods,
+            } => {
+                write!(f, "class {}", name)?;
+                if let Some(superclass) = superclass {
+                    write!(f, " < {:?}", superclass)?;
+                }
+                writeln!(f, " {{")?;
+                for method in methods {
+                    method.fmt(f)?;
+                }
+                writeln!(f, "}}")
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for FunctionDecl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let params: Vec<&str> = self.params.iter().map(|param| param.as_str()).collect();
+        writeln!(f, "fun {}({}) {{", self.name, params.join(", "))?;
+        for stmt in &self.body {
+            stmt.fmt(f)?;
         }
+        writeln!(f, "}}")
     }
 }
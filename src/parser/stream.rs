@@ -0,0 +1,151 @@
+//! Parsing input that is read a line at a time, such as standard input or
+//! a socket, without holding all of it in memory.
+
+use std::{collections::VecDeque, io::BufRead};
+
+use crate::{
+    lexer::{Lexer, LexerError, TokenKind},
+    parser::{error::ParserError, Parser, Stmt},
+};
+
+/// Parses a program from a reader one top-level statement at a time.
+///
+/// Input is read line by line until it holds complete top-level
+/// statements. That chunk is parsed by a [`Parser`] and then dropped, so
+/// memory is bounded by the longest statement rather than the whole input.
+/// Spans and line numbers count from the start of the input.
+///
+/// A statement is only known to be complete once the next token is read,
+/// since an `else` on a later line still belongs to it. The last statement
+/// is therefore yielded when the following one starts or the input ends.
+pub struct StreamParser<R> {
+    reader: R,
+    /// Whole lines read but not yet parsed.
+    buffer: String,
+    /// Line and byte offset of the start of `buffer` in the input.
+    line: usize,
+    offset: usize,
+    scan: Scan,
+    parsed: VecDeque<Result<Stmt, Vec<ParserError>>>,
+    /// A read failure, reported after the lines read before it.
+    failed: Option<std::io::Error>,
+    done: bool,
+}
+
+/// How far `buffer` has been lexed looking for the end of a statement.
+#[derive(Default)]
+struct Scan {
+    /// Bytes of `buffer` lexed so far. Stops at the start of a string or
+    /// comment that continues past the lines read.
+    scanned: usize,
+    /// Braces and parentheses opened and not yet closed.
+    depth: isize,
+    last: Option<TokenKind>,
+    /// End of the lines holding complete statements, waiting to see whether
+    /// the next token is an `else`.
+    cut: Option<usize>,
+}
+
+impl<R: BufRead> StreamParser<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: String::new(),
+            line: 1,
+            offset: 0,
+            scan: Scan::default(),
+            parsed: VecDeque::new(),
+            failed: None,
+            done: false,
+        }
+    }
+
+    /// Reads lines until the buffer starts with complete statements, and
+    /// returns them. Whatever is left is returned when the input ends or
+    /// fails, then `None`.
+    fn next_chunk(&mut self) -> Option<String> {
+        loop {
+            if let Some(cut) = self.scan_buffer() {
+                self.scan = Scan::default();
+                return Some(self.buffer.drain(..cut).collect());
+            }
+            match self.reader.read_line(&mut self.buffer) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(err) => {
+                    self.failed = Some(err);
+                    break;
+                }
+            }
+        }
+        self.done = true;
+        let rest = std::mem::take(&mut self.buffer);
+        (!rest.is_empty()).then_some(rest)
+    }
+
+    /// Lexes the lines not scanned yet, returning where to cut the buffer
+    /// once a statement boundary is confirmed.
+    fn scan_buffer(&mut self) -> Option<usize> {
+        let scan = &mut self.scan;
+        let rest = &self.buffer[scan.scanned..];
+        let mut lexer = Lexer::from_source(rest);
+        for token in lexer.by_ref() {
+            if token.kind == TokenKind::Eof {
+                break;
+            }
+            if let Some(cut) = scan.cut.take() {
+                if token.kind != TokenKind::ELSE {
+                    return Some(cut);
+                }
+            }
+            scan.depth += token.kind.nesting();
+            scan.last = Some(token.kind);
+        }
+        let unfinished = lexer.errors.iter().find_map(|err| match err {
+            LexerError::UnterminatedString(span) | LexerError::UnterminatedBlockComment(span) => {
+                Some(span.start)
+            }
+            _ => None,
+        });
+        match unfinished {
+            Some(start) => scan.scanned += start,
+            None => {
+                scan.scanned = self.buffer.len();
+                let ends_statement = matches!(
+                    scan.last,
+                    Some(TokenKind::Semicolon | TokenKind::RightBrace)
+                );
+                if scan.depth <= 0 && ends_statement {
+                    scan.cut = Some(self.buffer.len());
+                }
+            }
+        }
+        None
+    }
+
+    fn parse_chunk(&mut self, chunk: &str) {
+        let lexer = Lexer::from_source_at(chunk, self.line, self.offset);
+        self.parsed.extend(Parser::new(lexer));
+        self.line += chunk.matches('\n').count();
+        self.offset += chunk.len();
+    }
+}
+
+impl<R: BufRead> Iterator for StreamParser<R> {
+    type Item = Result<Stmt, Vec<ParserError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.parsed.is_empty() && !self.done {
+            if let Some(chunk) = self.next_chunk() {
+                self.parse_chunk(&chunk);
+            }
+        }
+        if let Some(stmt) = self.parsed.pop_front() {
+            return Some(stmt);
+        }
+        let err = self.failed.take()?;
+        Some(Err(vec![ParserError::LexerError(LexerError::SourceError(
+            err,
+        ))]))
+    }
+}
@@ -0,0 +1,100 @@
+//! Interactive prompt sharing one interpreter, and so one set of globals,
+//! across every entry.
+
+use std::path::PathBuf;
+
+use rustyline::{error::ReadlineError, DefaultEditor};
+
+use codecrafters_interpreter::{
+    diagnostics::{ErrorFormat, Renderer, ToDiagnostic},
+    lexer::{open_delimiters, Lexer},
+    parser::{stmt::StmtKind, Parser},
+    Interpreter, LoxError,
+};
+
+use crate::renderer;
+
+const PROMPT: &str = "> ";
+const CONTINUATION_PROMPT: &str = "... ";
+const SOURCE_NAME: &str = "<repl>";
+
+pub fn run(format: ErrorFormat) -> rustyline::Result<()> {
+    let mut editor = DefaultEditor::new()?;
+    let history = history_path();
+    if let Some(history) = &history {
+        // A missing history file just means this is the first session.
+        let _ = editor.load_history(history);
+    }
+
+    let mut interpreter = Interpreter::new();
+    let mut buffer = String::new();
+    loop {
+        let prompt = if buffer.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        };
+        match editor.readline(prompt) {
+            Ok(line) => {
+                buffer.push_str(&line);
+                buffer.push('\n');
+                if open_delimiters(&buffer) > 0 {
+                    continue;
+                }
+                let source = std::mem::take(&mut buffer);
+                if !source.trim().is_empty() {
+                    editor.add_history_entry(source.trim_end())?;
+                    eval(&mut interpreter, &source, format);
+                }
+            }
+            Err(ReadlineError::Interrupted) => buffer.clear(),
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err),
+        }
+    }
+
+    if let Some(history) = &history {
+        editor.save_history(history)?;
+    }
+    Ok(())
+}
+
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".lox_history"))
+}
+
+/// Runs one entry a statement at a time, printing the value of every
+/// expression statement. The first error is reported and ends the entry
+/// without leaving the prompt; statements before it keep their effects.
+fn eval(interpreter: &mut Interpreter, source: &str, format: ErrorFormat) {
+    let renderer = renderer(SOURCE_NAME, source, format);
+    let parser = Parser::new(Lexer::from_source(source)).with_optional_final_semicolon(true);
+    for stmt in parser {
+        let stmt = match stmt {
+            Ok(stmt) => stmt,
+            Err(errors) => return emit_all(&renderer, &errors),
+        };
+        let result = match &stmt.kind {
+            StmtKind::Expr(expr) => interpreter
+                .eval_expr(expr)
+                .map(|value| println!("{:?}", value)),
+            _ => interpreter.run_stmts(std::slice::from_ref(&stmt)),
+        };
+        match result {
+            Ok(()) => {}
+            Err(LoxError::Syntax(errors)) => return emit_all(&renderer, &errors),
+            Err(LoxError::Resolve(errors)) => return emit_all(&renderer, &errors),
+            Err(LoxError::Runtime(err)) => {
+                renderer.emit(&err);
+                renderer.emit_trace(interpreter.trace());
+                return;
+            }
+        }
+    }
+}
+
+fn emit_all(renderer: &Renderer, errors: &[impl ToDiagnostic]) {
+    for err in errors {
+        renderer.emit(err);
+    }
+}
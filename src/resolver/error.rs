@@ -0,0 +1,32 @@
+use thiserror::Error;
+
+use crate::lexer::Span;
+
+#[derive(Debug, Error)]
+pub enum ResolverError {
+    #[error("[line {}] Error at '{name}': Can't read local variable in its own initializer.", span.line)]
+    ReadInOwnInitializer { span: Span, name: String },
+
+    #[error("[line {}] Error at '{name}': Already a variable with this name in this scope.", span.line)]
+    AlreadyDeclared { span: Span, name: String },
+
+    #[error("[line {}] Error at 'return': Can't return from top-level code.", _0.line)]
+    TopLevelReturn(Span),
+
+    #[error("[line {}] Error at 'return': Can't return a value from an initializer.", _0.line)]
+    ReturnFromInitializer(Span),
+
+    #[error("[line {}] Error at 'this': Can't use 'this' outside of a class.", _0.line)]
+    ThisOutsideClass(Span),
+
+    #[error("[line {}] Error at 'super': Can't use 'super' outside of a class.", _0.line)]
+    SuperOutsideClass(Span),
+
+    #[error("[line {}] Error at 'super': Can't use 'super' in a class with no superclass.", _0.line)]
+    SuperWithoutSuperclass(Span),
+
+    #[error("[line {}] Error at '{name}': A class can't inherit from itself.", span.line)]
+    InheritsFromItself { span: Span, name: String },
+}
+
+pub type ResolverResult<T> = std::result::Result<T, ResolverError>;
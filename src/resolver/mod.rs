@@ -0,0 +1,284 @@
+pub use error::{ResolverError, ResolverResult};
+
+use crate::{
+    interpreter::Interpreter,
+    lexer::Span,
+    parser::{
+        expr::{ExprKind, Ident, IdentMap},
+        stmt::{FunctionDecl, StmtKind},
+        Expr, Stmt, STACK_RED_ZONE, STACK_SEGMENT,
+    },
+};
+
+pub mod error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FunctionKind {
+    None,
+    Function,
+    Method,
+    Initializer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClassKind {
+    None,
+    Class,
+    Subclass,
+}
+
+/// Static pass run between parsing and interpretation. Binds every local
+/// variable reference to the scope it lives in and reports misuse of
+/// declarations, `return`, `this` and `super`. Run by
+/// [`Interpreter::run_stmts`] and [`Interpreter::eval_expr`].
+pub(crate) struct Resolver<'a> {
+    interpreter: &'a mut Interpreter,
+    /// Block scopes being resolved, innermost last. A name maps to `false`
+    /// while its initializer is being resolved. Globals are not tracked.
+    scopes: Vec<IdentMap<bool>>,
+    function: FunctionKind,
+    class: ClassKind,
+    errors: Vec<ResolverError>,
+}
+
+impl<'a> Resolver<'a> {
+    pub(crate) fn new(interpreter: &'a mut Interpreter) -> Self {
+        Self {
+            interpreter,
+            scopes: vec![],
+            function: FunctionKind::None,
+            class: ClassKind::None,
+            errors: vec![],
+        }
+    }
+
+    /// Resolves `stmts`, returning every error found.
+    pub(crate) fn resolve(mut self, stmts: &[Stmt]) -> Result<(), Vec<ResolverError>> {
+        self.resolve_stmts(stmts);
+        self.finish()
+    }
+
+    /// Resolves a lone expression evaluated in the global scope.
+    pub(crate) fn resolve_expression(mut self, expr: &Expr) -> Result<(), Vec<ResolverError>> {
+        self.resolve_expr(expr);
+        self.finish()
+    }
+
+    fn finish(self) -> Result<(), Vec<ResolverError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn resolve_stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.resolve_stmt(stmt);
+        }
+    }
+
+    fn resolve_stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Expr(expr) | StmtKind::Print(expr) => self.resolve_expr(expr),
+            StmtKind::Declaration(name, initializer) => {
+                self.declare(name, stmt.span);
+                self.resolve_expr(initializer);
+                self.define(name);
+            }
+            StmtKind::Block(stmts) => {
+                self.begin_scope();
+                self.resolve_stmts(stmts);
+                self.end_scope();
+            }
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.resolve_expr(condition);
+                self.resolve_stmt(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.resolve_stmt(else_branch);
+                }
+            }
+            StmtKind::While { condition, body } => {
+                self.resolve_expr(condition);
+                self.resolve_stmt(body);
+            }
+            StmtKind::Function(decl) => {
+                self.declare(&decl.name, decl.span);
+                self.define(&decl.name);
+                self.resolve_function(decl, FunctionKind::Function);
+            }
+            StmtKind::Return(value) => {
+                if self.function == FunctionKind::None {
+                    self.errors.push(ResolverError::TopLevelReturn(stmt.span));
+                }
+                if let Some(value) = value {
+                    if self.function == FunctionKind::Initializer {
+                        self.errors
+                            .push(ResolverError::ReturnFromInitializer(stmt.span));
+                    }
+                    self.resolve_expr(value);
+                }
+            }
+            StmtKind::Class {
+                name,
+                superclass,
+                methods,
+            } => {
+                let enclosing = std::mem::replace(&mut self.class, ClassKind::Class);
+                self.declare(name, stmt.span);
+                self.define(name);
+
+                if let Some(superclass) = superclass {
+                    if matches!(&superclass.kind, ExprKind::Ident(parent) if parent == name) {
+                        self.errors.push(ResolverError::InheritsFromItself {
+                            span: superclass.span,
+                            name: name.to_string(),
+                        });
+                    }
+                    self.class = ClassKind::Subclass;
+                    self.resolve_expr(superclass);
+                    self.begin_scope();
+                    self.define(&Ident::super_());
+                }
+
+                self.begin_scope();
+                self.define(&Ident::this());
+                for method in methods {
+                    let kind = if method.name.as_str() == "init" {
+                        FunctionKind::Initializer
+                    } else {
+                        FunctionKind::Method
+                    };
+                    self.resolve_function(method, kind);
+                }
+                self.end_scope();
+
+                if superclass.is_some() {
+                    self.end_scope();
+                }
+                self.class = enclosing;
+            }
+        }
+    }
+
+    fn resolve_function(&mut self, decl: &FunctionDecl, kind: FunctionKind) {
+        let enclosing = std::mem::replace(&mut self.function, kind);
+        self.begin_scope();
+        for param in &decl.params {
+            self.declare(param, decl.span);
+            self.define(param);
+        }
+        self.resolve_stmts(&decl.body);
+        self.end_scope();
+        self.function = enclosing;
+    }
+
+    fn resolve_expr(&mut self, expr: &Expr) {
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || {
+            self.resolve_expr_kind(expr)
+        })
+    }
+
+    fn resolve_expr_kind(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Literal(_) => {}
+            ExprKind::Unary { operand, .. } | ExprKind::Group(operand) => {
+                self.resolve_expr(operand)
+            }
+            ExprKind::Binary { left, right, .. } | ExprKind::Logical { left, right, .. } => {
+                self.resolve_expr(left);
+                self.resolve_expr(right);
+            }
+            ExprKind::Ident(name) => {
+                if self
+                    .scopes
+                    .last()
+                    .is_some_and(|scope| scope.get(name) == Some(&false))
+                {
+                    self.errors.push(ResolverError::ReadInOwnInitializer {
+                        span: expr.span,
+                        name: name.to_string(),
+                    });
+                }
+                self.resolve_local(expr, name);
+            }
+            ExprKind::Assignment(name, value) => {
+                self.resolve_expr(value);
+                self.resolve_local(expr, name);
+            }
+            ExprKind::Call { callee, args } => {
+                self.resolve_expr(callee);
+                for arg in args {
+                    self.resolve_expr(arg);
+                }
+            }
+            ExprKind::Get { object, .. } => self.resolve_expr(object),
+            ExprKind::Set { object, value, .. } => {
+                self.resolve_expr(value);
+                self.resolve_expr(object);
+            }
+            ExprKind::This => {
+                if self.class == ClassKind::None {
+                    self.errors.push(ResolverError::ThisOutsideClass(expr.span));
+                    return;
+                }
+                self.resolve_local(expr, &Ident::this());
+            }
+            ExprKind::Super { .. } => {
+                match self.class {
+                    ClassKind::None => self
+                        .errors
+                        .push(ResolverError::SuperOutsideClass(expr.span)),
+                    ClassKind::Class => self
+                        .errors
+                        .push(ResolverError::SuperWithoutSuperclass(expr.span)),
+                    ClassKind::Subclass => {}
+                }
+                self.resolve_local(expr, &Ident::super_());
+            }
+        }
+    }
+
+    /// Records how many scopes separate `expr` from the declaration of `name`.
+    /// Names not found in any scope are left to be looked up as globals.
+    fn resolve_local(&mut self, expr: &Expr, name: &Ident) {
+        if let Some(depth) = self
+            .scopes
+            .iter()
+            .rev()
+            .position(|scope| scope.contains_key(name))
+        {
+            self.interpreter.resolve(expr.id, depth);
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(IdentMap::default());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &Ident, span: Span) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+        if scope.insert(name.clone(), false).is_some() {
+            self.errors.push(ResolverError::AlreadyDeclared {
+                span,
+                name: name.to_string(),
+            });
+        }
+    }
+
+    fn define(&mut self, name: &Ident) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.clone(), true);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Native functions available to every script.
+
+mod common;
+
+use codecrafters_interpreter::Interpreter;
+use common::{output, runtime_error};
+
+#[test]
+fn substr_takes_a_character_range() {
+    let source = r#"
+print substr("hello", 1, 4);
+print substr("hello", 0, 0);
+print substr("hello", 5, 5);
+print substr("héllo wörld", 1, 8);
+"#;
+    assert_eq!(output(source).unwrap(), "ell\n\n\néllo wö\n");
+}
+
+#[test]
+fn substr_rejects_ranges_outside_the_string() {
+    for (source, message) in [
+        (
+            "substr(\"abc\", 0, 4);",
+            "substr() range 0..4 is out of bounds for a string of length 3.",
+        ),
+        (
+            "substr(\"abc\", 2, 1);",
+            "substr() range 2..1 is out of bounds for a string of length 3.",
+        ),
+        (
+            "substr(\"héé\", 0, 4);",
+            "substr() range 0..4 is out of bounds for a string of length 3.",
+        ),
+        (
+            "substr(\"abc\", -1, 2);",
+            "substr() expects a non-negative integer as argument 2.",
+        ),
+        (
+            "substr(\"abc\", 0, 1.5);",
+            "substr() expects a non-negative integer as argument 3.",
+        ),
+    ] {
+        assert_eq!(
+            runtime_error(source),
+            format!("{message}\n[line 1]"),
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn index_of_counts_characters() {
+    let source = r#"
+print indexOf("hello", "l");
+print indexOf("héllo", "l");
+print indexOf("日本語", "語");
+print indexOf("hello", "z");
+print indexOf("hello", "");
+"#;
+    assert_eq!(output(source).unwrap(), "2\n2\n2\n-1\n0\n");
+}
+
+#[test]
+fn split_separates_on_the_separator_or_into_characters() {
+    let source = r#"
+print split("a,b,,c", ",");
+print len(split("a,b,,c", ","));
+print split("héj", "");
+print len(split("", ""));
+print at(split("x-y", "-"), 1);
+"#;
+    assert_eq!(output(source).unwrap(), "[a, b, , c]\n4\n[h, é, j]\n0\ny\n");
+}
+
+#[test]
+fn seed_makes_random_reproducible() {
+    let source = r#"
+seed(42);
+var a = random();
+var b = random();
+seed(42);
+print a == random();
+print b == random();
+print a != b;
+print a >= 0 and a < 1;
+"#;
+    assert_eq!(output(source).unwrap(), "true\ntrue\ntrue\ntrue\n");
+
+    let mut first = Interpreter::with_output(std::io::sink());
+    let mut second = Interpreter::with_output(std::io::sink());
+    first.seed_random(7);
+    second.seed_random(7);
+    let draw = "var r = random() + random();";
+    first.run(draw).unwrap();
+    second.run(draw).unwrap();
+    assert_eq!(
+        format!("{:?}", first.get_global("r")),
+        format!("{:?}", second.get_global("r"))
+    );
+}
+
+#[test]
+fn conversions_between_strings_and_numbers() {
+    let source = r#"
+print num(" 2.5 ") + 1;
+print num(3);
+print str(1.5) + "x";
+print str(nil);
+print type(1);
+print type("s");
+print type(nil);
+print type(clock);
+print type(split("a", ""));
+"#;
+    assert_eq!(
+        output(source).unwrap(),
+        "3.5\n3\n1.5x\nnil\nnumber\nstring\nnil\nfunction\nlist\n"
+    );
+}
+
+#[test]
+fn num_rejects_text_that_is_not_a_finite_number() {
+    for text in ["abc", "", "1.2.3", "inf", "-infinity", "NaN"] {
+        assert_eq!(
+            runtime_error(&format!("num(\"{text}\");")),
+            format!("num() could not parse \"{text}\" as a number.\n[line 1]"),
+        );
+    }
+}
+
+#[test]
+fn builtins_check_argument_types() {
+    for (source, message) in [
+        ("len(1);", "len() expects a string or list as argument 1."),
+        ("upper(nil);", "upper() expects a string as argument 1."),
+        (
+            "indexOf(\"a\", 1);",
+            "indexOf() expects a string as argument 2.",
+        ),
+        (
+            "num(true);",
+            "num() expects a string or number as argument 1.",
+        ),
+        ("at(\"abc\", 0);", "at() expects a list as argument 1."),
+        ("sqrt(\"4\");", "sqrt() expects a number as argument 1."),
+        (
+            "at(split(\"a\", \"\"), 1);",
+            "at() index 1 is out of bounds for a list of length 1.",
+        ),
+    ] {
+        assert_eq!(
+            runtime_error(source),
+            format!("{message}\n[line 1]"),
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn builtins_check_their_arity() {
+    for (source, message) in [
+        ("substr(\"abc\", 1);", "Expected 3 arguments but got 2."),
+        ("clock(1);", "Expected 0 arguments but got 1."),
+        ("len();", "Expected 1 arguments but got 0."),
+    ] {
+        assert_eq!(
+            runtime_error(source),
+            format!("{message}\n[line 1]"),
+            "{source}"
+        );
+    }
+}
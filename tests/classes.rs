@@ -0,0 +1,175 @@
+//! Classes, instances, methods and inheritance.
+
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn instances_hold_fields_and_call_methods() {
+    let source = r#"
+class Counter {
+  bump() {
+    this.count = this.count + 1;
+    return this.count;
+  }
+}
+var c = Counter();
+c.count = 10;
+c.bump();
+print c.bump();
+print Counter;
+print c;
+"#;
+    assert_eq!(output(source).unwrap(), "12\nCounter\nCounter instance\n");
+}
+
+#[test]
+fn init_runs_on_construction_and_returns_this() {
+    let source = r#"
+class Point {
+  init(x, y) {
+    this.x = x;
+    this.y = y;
+  }
+}
+var p = Point(1, 2);
+print p.x + p.y;
+print p.init(3, 4) == p;
+print p.x;
+"#;
+    assert_eq!(output(source).unwrap(), "3\ntrue\n3\n");
+}
+
+#[test]
+fn an_early_return_from_init_still_returns_this() {
+    let source = r#"
+class A {
+  init() {
+    this.done = true;
+    return;
+  }
+}
+var a = A();
+print a.init() == a;
+"#;
+    assert_eq!(output(source).unwrap(), "true\n");
+}
+
+#[test]
+fn bound_methods_remember_their_instance() {
+    let source = r#"
+class Person {
+  init(name) { this.name = name; }
+  greet() { print "hi " + this.name; }
+}
+var greet = Person("ann").greet;
+var bob = Person("bob");
+bob.greet = greet;
+greet();
+bob.greet();
+"#;
+    assert_eq!(output(source).unwrap(), "hi ann\nhi ann\n");
+}
+
+#[test]
+fn fields_shadow_methods() {
+    let source = r#"
+class A { f() { return "method"; } }
+var a = A();
+a.f = "field";
+print a.f;
+"#;
+    assert_eq!(output(source).unwrap(), "field\n");
+}
+
+#[test]
+fn subclasses_inherit_and_override_methods() {
+    let source = r#"
+class Animal {
+  init(name) { this.name = name; }
+  speak() { return this.name + " makes a sound"; }
+  describe() { return this.speak(); }
+}
+class Dog < Animal {
+  speak() { return this.name + " barks"; }
+}
+print Dog("rex").describe();
+print Dog("rex").name;
+"#;
+    assert_eq!(output(source).unwrap(), "rex barks\nrex\n");
+}
+
+#[test]
+fn super_calls_the_superclass_method() {
+    let source = r#"
+class A {
+  method() { return "A"; }
+}
+class B < A {
+  method() { return "B then " + super.method(); }
+}
+class C < B {}
+print C().method();
+"#;
+    assert_eq!(output(source).unwrap(), "B then A\n");
+}
+
+#[test]
+fn super_works_from_nested_closures() {
+    let source = r#"
+class A {
+  say() { print "A.say " + this.tag; }
+}
+class B < A {
+  init() { this.tag = "from B"; }
+  later() {
+    fun outer() {
+      fun inner() {
+        super.say();
+      }
+      return inner;
+    }
+    return outer();
+  }
+}
+var f = B().later();
+f();
+"#;
+    assert_eq!(output(source).unwrap(), "A.say from B\n");
+}
+
+#[test]
+fn reading_a_missing_property_is_an_error() {
+    assert_eq!(
+        runtime_error("class A {}\nprint A().missing;"),
+        "Undefined property 'missing'.\n[line 2]"
+    );
+    assert_eq!(
+        runtime_error("class A {}\nclass B < A {\n  f() { return super.missing; }\n}\nB().f();"),
+        "Undefined property 'missing'.\n[line 3]"
+    );
+}
+
+#[test]
+fn only_instances_have_properties_and_fields() {
+    assert_eq!(
+        runtime_error("var x = 1;\nprint x.y;"),
+        "Only instances have properties.\n[line 2]"
+    );
+    assert_eq!(
+        runtime_error("var x = \"s\";\nx.y = 1;"),
+        "Only instances have fields.\n[line 2]"
+    );
+}
+
+#[test]
+fn superclass_must_be_a_class() {
+    assert_eq!(
+        runtime_error("var NotAClass = \"nope\";\nclass A < NotAClass {}"),
+        "Superclass must be a class.\n[line 2]"
+    );
+    assert_eq!(
+        runtime_error("fun f() {}\nclass A < f {}"),
+        "Superclass must be a class.\n[line 2]"
+    );
+}
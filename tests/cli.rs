@@ -0,0 +1,76 @@
+//! The binary reads source from a file, standard input or `-e`, and reports
+//! failures through its exit code.
+
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn interpreter(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_codecrafters-interpreter"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+#[test]
+fn eval_flag_runs_the_given_code() {
+    let output = interpreter(&["run", "-e", "print 1 + 2;"], "");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "3\n");
+}
+
+#[test]
+fn eval_flag_accepts_code_starting_with_a_hyphen() {
+    let output = interpreter(&["evaluate", "-e", "-1"], "");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "-1\n");
+}
+
+#[test]
+fn hyphen_reads_standard_input() {
+    let output = interpreter(&["run", "-"], "var a = \"in\";\nprint a;\n");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "in\n");
+
+    let output = interpreter(&["tokenize", "-"], "(");
+    assert_eq!(stdout(&output), "LEFT_PAREN ( null\nEOF  null\n");
+}
+
+#[test]
+fn exit_codes_tell_static_and_runtime_errors_apart() {
+    let output = interpreter(&["run", "-e", "print ;"], "");
+    assert_eq!(output.status.code(), Some(65));
+
+    let output = interpreter(&["run", "-"], "print -\"a\";");
+    assert_eq!(output.status.code(), Some(70));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Operand must be a number."));
+}
+
+#[test]
+fn file_and_eval_flag_are_mutually_exclusive() {
+    let output = interpreter(&["run", "-e", "print 1;", "file.lox"], "");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn repl_continues_entries_with_open_delimiters() {
+    let input = "fun f() {\n  return \"{\";\n}\nf()\nprint (1 +\n2);\n";
+    let output = interpreter(&["repl"], input);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "{\n3\n");
+}
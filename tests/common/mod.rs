@@ -0,0 +1,47 @@
+//! Helpers shared by the integration tests. Not every test uses all of them.
+#![allow(dead_code)]
+
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+use codecrafters_interpreter::{Interpreter, LoxError, LoxResult};
+
+/// A writer whose contents can still be read after it is handed to an
+/// [`Interpreter`].
+#[derive(Clone, Default)]
+pub struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl SharedOutput {
+    pub fn contents(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs `source` in a fresh interpreter and returns what it printed.
+pub fn output(source: &str) -> LoxResult<String> {
+    let out = SharedOutput::default();
+    Interpreter::with_output(out.clone()).run(source)?;
+    Ok(out.contents())
+}
+
+/// Runs `source` in a fresh interpreter whose output is discarded.
+pub fn run(source: &str) -> LoxResult<()> {
+    Interpreter::with_output(std::io::sink()).run(source)
+}
+
+/// Runs `source`, expecting a runtime error, and returns its message.
+pub fn runtime_error(source: &str) -> String {
+    match run(source) {
+        Err(err @ LoxError::Runtime(_)) => err.to_string(),
+        other => panic!("expected a runtime error from `{source}`, got {other:?}"),
+    }
+}
@@ -0,0 +1,116 @@
+//! `if`, `while` and `for` statements.
+
+mod common;
+
+use codecrafters_interpreter::LoxError;
+use common::{output, runtime_error};
+
+#[test]
+fn if_runs_the_branch_the_condition_picks() {
+    let source = r#"
+if (true) print "then"; else print "else";
+if (nil) print "then"; else print "else";
+if (0) print "zero is truthy";
+if (false) print "skipped";
+if ("") { print "block"; print "branch"; }
+"#;
+    assert_eq!(
+        output(source).unwrap(),
+        "then\nelse\nzero is truthy\nblock\nbranch\n"
+    );
+}
+
+#[test]
+fn else_binds_to_the_nearest_if() {
+    let source = r#"
+if (true) if (false) print "inner"; else print "inner else";
+if (false) if (true) print "inner"; else print "inner else";
+print "done";
+"#;
+    assert_eq!(output(source).unwrap(), "inner else\ndone\n");
+}
+
+#[test]
+fn while_repeats_until_the_condition_is_false() {
+    let source = r#"
+var i = 0;
+while (i < 3) {
+  print i;
+  i = i + 1;
+}
+while (false) print "never";
+print i;
+"#;
+    assert_eq!(output(source).unwrap(), "0\n1\n2\n3\n");
+}
+
+#[test]
+fn for_clauses_can_each_be_left_out() {
+    let source = r#"
+for (var i = 0; i < 2; i = i + 1) print i;
+
+var j = 10;
+for (; j < 12; j = j + 1) print j;
+
+for (var k = 20; k < 22;) {
+  print k;
+  k = k + 1;
+}
+
+fun firstOver(limit) {
+  for (var n = 0;; n = n + 1) {
+    if (n * n > limit) return n;
+  }
+}
+print firstOver(50);
+
+fun count() {
+  var m = 30;
+  for (;;) {
+    if (m == 32) return;
+    print m;
+    m = m + 1;
+  }
+}
+count();
+"#;
+    assert_eq!(output(source).unwrap(), "0\n1\n10\n11\n20\n21\n8\n30\n31\n");
+}
+
+#[test]
+fn for_loop_variables_are_scoped_to_the_loop() {
+    let source = r#"
+var i = "outer";
+for (var i = 0; i < 2; i = i + 1) {
+  var inner = i * 10;
+  print inner;
+}
+print i;
+"#;
+    assert_eq!(output(source).unwrap(), "0\n10\nouter\n");
+
+    assert_eq!(
+        runtime_error("for (var i = 0; i < 1; i = i + 1) {}\nprint i;"),
+        "Undefined variable 'i'.\n[line 2]"
+    );
+}
+
+#[test]
+fn for_without_a_declaration_updates_the_outer_variable() {
+    let source = "var i;\nfor (i = 0; i < 3; i = i + 1) {}\nprint i;";
+    assert_eq!(output(source).unwrap(), "3\n");
+}
+
+#[test]
+fn loop_bodies_cannot_be_declarations() {
+    for source in [
+        "while (true) var x = 1;",
+        "for (;;) fun f() {}",
+        "if (true) class A {}",
+    ] {
+        assert!(
+            matches!(output(source), Err(LoxError::Syntax(_))),
+            "{source}"
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Rendering errors against the source they came from.
+
+use codecrafters_interpreter::{
+    diagnostics::{Diagnostic, ErrorFormat, Renderer, ToDiagnostic},
+    lexer::{Lexer, LexerError, Span},
+    parser::Parser,
+    Interpreter,
+};
+
+fn human(source: &str, diagnostic: &Diagnostic) -> String {
+    Renderer::new("test.lox", source, ErrorFormat::Human).render(diagnostic)
+}
+
+fn lexer_error(source: &str) -> LexerError {
+    let mut lexer = Lexer::from_source(source);
+    lexer.by_ref().for_each(drop);
+    lexer.errors.remove(0)
+}
+
+#[test]
+fn header_and_caret_point_at_the_span() {
+    let source = "var x = 1;\nprint x + @;\n";
+    let rendered = human(source, &lexer_error(source).to_diagnostic());
+    assert_eq!(
+        rendered,
+        "\
+error: Unexpected character: @
+ --> test.lox:2:11
+  |
+2 | print x + @;
+  |           ^"
+    );
+}
+
+#[test]
+fn gutter_widens_with_the_line_number() {
+    let source = format!("{}print @;", "\n".repeat(99));
+    let rendered = human(&source, &lexer_error(&source).to_diagnostic());
+    assert_eq!(
+        rendered,
+        "\
+error: Unexpected character: @
+   --> test.lox:100:7
+    |
+100 | print @;
+    |       ^"
+    );
+}
+
+#[test]
+fn carets_count_characters_not_bytes() {
+    let source = "print \"é\" + ∑;";
+    let rendered = human(source, &lexer_error(source).to_diagnostic());
+    assert!(
+        rendered.ends_with("\n1 | print \"é\" + ∑;\n  |             ^"),
+        "{rendered}"
+    );
+
+    // Bytes 6..13 are `"héllo`, six characters.
+    let source = "print \"héllo\";";
+    let diagnostic = Diagnostic::new("message", Some(Span::new(6, 13, 1, 7)), "summary");
+    assert!(human(source, &diagnostic).ends_with("\n  |       ^^^^^^"));
+}
+
+#[test]
+fn tabs_before_the_span_are_kept() {
+    let source = "\tprint @;";
+    let rendered = human(source, &lexer_error(source).to_diagnostic());
+    assert!(
+        rendered.ends_with("\n1 | \tprint @;\n  | \t      ^"),
+        "{rendered:?}"
+    );
+}
+
+#[test]
+fn span_at_the_end_of_the_source() {
+    let source = "print 1";
+    let errors = Parser::new(Lexer::from_source(source)).parse().unwrap_err();
+    let rendered = human(source, &errors[0].to_diagnostic());
+    assert_eq!(
+        rendered,
+        "\
+error: Expect ';' after value.
+ --> test.lox:1:8
+  |
+1 | print 1
+  |        ^"
+    );
+
+    let diagnostic = Diagnostic::new("message", Some(Span::new(50, 50, 2, 1)), "summary");
+    assert!(human("a\n", &diagnostic).ends_with("\n2 | \n  | ^"));
+}
+
+#[test]
+fn notes_and_help_follow_the_snippet() {
+    let source = "print (1";
+    let errors = Parser::new(Lexer::from_source(source)).parse().unwrap_err();
+    assert_eq!(
+        human(source, &errors[0].to_diagnostic()),
+        "\
+error: Unmatched parens
+ --> test.lox:1:7
+  |
+1 | print (1
+  |       ^
+  = note: this `(` is never closed
+  = help: add a closing `)`"
+    );
+
+    let diagnostic = Diagnostic::new("message", None, "summary")
+        .with_note("first")
+        .with_note("second")
+        .with_help("try this");
+    assert_eq!(
+        human(source, &diagnostic),
+        "error: message\n --> test.lox\n  = note: first\n  = note: second\n  = help: try this"
+    );
+}
+
+#[test]
+fn color_is_only_used_when_enabled() {
+    let source = "print @;";
+    let diagnostic = lexer_error(source).to_diagnostic().with_help("remove it");
+    let renderer = Renderer::new("test.lox", source, ErrorFormat::Human);
+    assert!(!renderer.render(&diagnostic).contains('\x1b'));
+    assert!(!renderer
+        .with_color(false)
+        .render(&diagnostic)
+        .contains('\x1b'));
+
+    let colored = renderer.with_color(true).render(&diagnostic);
+    assert!(colored.starts_with("\x1b[1;31merror\x1b[0m"), "{colored:?}");
+    let plain: String = colored
+        .split('\x1b')
+        .enumerate()
+        .map(|(i, part)| {
+            if i == 0 {
+                part
+            } else {
+                &part[part.find('m').unwrap() + 1..]
+            }
+        })
+        .collect();
+    assert_eq!(plain, renderer.render(&diagnostic));
+}
+
+#[test]
+fn plain_format_uses_the_summary() {
+    let source = "var x = 1;\nprint x + @;\n";
+    let err = lexer_error(source);
+    let renderer = Renderer::new("test.lox", source, ErrorFormat::Plain).with_color(true);
+    assert_eq!(
+        renderer.render(&err.to_diagnostic()),
+        "[line 2] Error: Unexpected character: @"
+    );
+    assert_eq!(renderer.render(&err.to_diagnostic()), err.to_string());
+}
+
+#[test]
+fn traces_list_each_frame() {
+    let source = "fun f() {\n  return -nil;\n}\nprint f();";
+    let mut interpreter = Interpreter::with_output(std::io::sink());
+    interpreter.run(source).unwrap_err();
+
+    let plain =
+        Renderer::new("t.lox", source, ErrorFormat::Plain).render_trace(interpreter.trace());
+    assert_eq!(
+        plain,
+        "  in return statement [line 2]\n  in call to 'f' [line 4]\n  in print statement [line 4]\n"
+    );
+    let human =
+        Renderer::new("t.lox", source, ErrorFormat::Human).render_trace(interpreter.trace());
+    assert_eq!(
+        human,
+        "  in return statement at t.lox:2:3\n  in call to 'f' at t.lox:4:7\n  in print statement at t.lox:4:1\n"
+    );
+}
+
+#[test]
+fn long_traces_are_shortened() {
+    let source = "fun f(n) { if (n == 0) return -nil; return f(n - 1); }\nf(100);";
+    let mut interpreter = Interpreter::with_output(std::io::sink());
+    interpreter.run(source).unwrap_err();
+    let trace =
+        Renderer::new("t.lox", source, ErrorFormat::Plain).render_trace(interpreter.trace());
+    let lines: Vec<_> = trace.lines().collect();
+    assert_eq!(lines.len(), 33);
+    assert!(lines[32].starts_with("  ... ") && lines[32].ends_with(" more frames"));
+}
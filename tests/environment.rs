@@ -0,0 +1,90 @@
+//! Variables live in a chain of scopes, from the innermost block out to
+//! the globals.
+
+mod common;
+
+use common::{output, runtime_error};
+
+#[test]
+fn assignment_in_a_block_changes_the_outer_variable() {
+    let source = r#"
+var a = "before";
+{
+  a = "after";
+  {
+    a = a + " twice";
+  }
+}
+print a;
+"#;
+    assert_eq!(output(source).unwrap(), "after twice\n");
+}
+
+#[test]
+fn inner_declarations_shadow_outer_ones() {
+    let source = r#"
+var a = "global";
+{
+  var a = "outer";
+  {
+    var a = "inner";
+    print a;
+  }
+  print a;
+}
+print a;
+"#;
+    assert_eq!(output(source).unwrap(), "inner\nouter\nglobal\n");
+}
+
+#[test]
+fn shadowing_variables_are_dropped_with_their_block() {
+    let source = r#"
+var a = 1;
+{
+  var a = 2;
+  a = 3;
+}
+print a;
+"#;
+    assert_eq!(output(source).unwrap(), "1\n");
+}
+
+#[test]
+fn globals_can_be_redeclared() {
+    let source = "var a = 1;\nvar a;\nprint a;\nvar a = \"again\";\nprint a;";
+    assert_eq!(output(source).unwrap(), "nil\nagain\n");
+}
+
+#[test]
+fn assignment_is_an_expression_that_writes_the_variable() {
+    let source = r#"
+var a;
+var b;
+print a = 1;
+a = b = 2;
+print a + b;
+{
+  var c;
+  print c = "local";
+  print c;
+}
+"#;
+    assert_eq!(output(source).unwrap(), "1\n4\nlocal\nlocal\n");
+}
+
+#[test]
+fn undeclared_variables_cannot_be_assigned_or_read() {
+    assert_eq!(
+        runtime_error("missing = 1;"),
+        "Undefined variable 'missing'.\n[line 1]"
+    );
+    assert_eq!(
+        runtime_error("{\n  print missing;\n}"),
+        "Undefined variable 'missing'.\n[line 2]"
+    );
+    assert_eq!(
+        runtime_error("{ var a = 1; }\nprint a;"),
+        "Undefined variable 'a'.\n[line 2]"
+    );
+}
@@ -0,0 +1,153 @@
+//! Function calls, closures, recursion and the limits on arguments.
+
+mod common;
+
+use codecrafters_interpreter::LoxError;
+use common::{output, run, runtime_error};
+
+fn syntax_errors(source: &str) -> Vec<String> {
+    match run(source) {
+        Err(LoxError::Syntax(errors)) => errors.iter().map(ToString::to_string).collect(),
+        other => panic!("expected syntax errors from a long list, got {other:?}"),
+    }
+}
+
+fn list(prefix: &str, count: usize) -> String {
+    (0..count)
+        .map(|i| format!("{prefix}{i}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[test]
+fn closures_capture_their_enclosing_variables() {
+    let source = r#"
+fun makeCounter() {
+  var i = 0;
+  fun count() {
+    i = i + 1;
+    return i;
+  }
+  return count;
+}
+var a = makeCounter();
+var b = makeCounter();
+a();
+print a();
+print b();
+"#;
+    assert_eq!(output(source).unwrap(), "2\n1\n");
+}
+
+#[test]
+fn closures_share_a_captured_variable() {
+    let source = r#"
+var get;
+var set;
+{
+  var x = "before";
+  fun g() { return x; }
+  fun s(value) { x = value; }
+  get = g;
+  set = s;
+}
+set("after");
+print get();
+"#;
+    assert_eq!(output(source).unwrap(), "after\n");
+}
+
+#[test]
+fn functions_can_recurse() {
+    let source = r#"
+fun fib(n) {
+  if (n < 2) return n;
+  return fib(n - 1) + fib(n - 2);
+}
+print fib(15);
+
+fun isEven(n) { if (n == 0) return true; return isOdd(n - 1); }
+fun isOdd(n) { if (n == 0) return false; return isEven(n - 1); }
+print isEven(10);
+"#;
+    assert_eq!(output(source).unwrap(), "610\ntrue\n");
+}
+
+#[test]
+fn return_unwinds_loops_and_blocks() {
+    let source = r#"
+fun find(limit) {
+  for (var i = 0; i < 3; i = i + 1) {
+    var j = 0;
+    while (true) {
+      {
+        if (i * 10 + j == limit) return i * 10 + j;
+      }
+      j = j + 1;
+      if (j == 3) return "stop at " + "row";
+    }
+  }
+  return "none";
+}
+print find(1);
+print find(12);
+var after = "still running";
+print after;
+"#;
+    assert_eq!(output(source).unwrap(), "1\nstop at row\nstill running\n");
+}
+
+#[test]
+fn functions_without_a_return_value_give_nil() {
+    let source = "fun f() {}\nfun g() { return; }\nprint f();\nprint g();\nprint f;";
+    assert_eq!(output(source).unwrap(), "nil\nnil\n<fn f>\n");
+}
+
+#[test]
+fn calls_must_match_the_arity() {
+    for (source, message) in [
+        (
+            "fun f(a, b) {}\nf(1);",
+            "Expected 2 arguments but got 1.\n[line 2]",
+        ),
+        (
+            "fun f() {}\nf(1, 2);",
+            "Expected 0 arguments but got 2.\n[line 2]",
+        ),
+        (
+            "class A { init(x) {} }\nA();",
+            "Expected 1 arguments but got 0.\n[line 2]",
+        ),
+        (
+            "\"not a function\"();",
+            "Can only call functions and classes.\n[line 1]",
+        ),
+    ] {
+        assert_eq!(runtime_error(source), message, "{source}");
+    }
+}
+
+#[test]
+fn up_to_255_arguments_are_allowed() {
+    let source = format!(
+        "fun f({}) {{ return p254; }}\nprint f({});",
+        list("p", 255),
+        list("", 255)
+    );
+    assert_eq!(output(&source).unwrap(), "254\n");
+}
+
+#[test]
+fn more_than_255_arguments_or_parameters_are_errors() {
+    let source = format!("fun f() {{}}\nf({});", list("", 256));
+    assert_eq!(
+        syntax_errors(&source),
+        ["[line 2] Error at '255': Can't have more than 255 arguments."]
+    );
+
+    let source = format!("fun f({}) {{}}", list("p", 256));
+    assert_eq!(
+        syntax_errors(&source),
+        ["[line 1] Error at 'p255': Can't have more than 255 parameters."]
+    );
+}
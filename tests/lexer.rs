@@ -0,0 +1,288 @@
+//! Tokenizing sources from memory.
+
+use std::borrow::Cow;
+
+use codecrafters_interpreter::lexer::{
+    open_delimiters, Lexer, LexerError, Source, Token, TokenKind, TokenLiteral,
+};
+
+fn tokens(source: &str) -> (Vec<Token<'_>>, Vec<LexerError>) {
+    let mut lexer = Lexer::from_source(source);
+    let tokens = lexer.parse_to_end();
+    (tokens, lexer.errors)
+}
+
+fn kinds_and_lines(tokens: &[Token]) -> Vec<(TokenKind, usize)> {
+    tokens
+        .iter()
+        .map(|token| (token.kind, token.span.line))
+        .collect()
+}
+
+#[test]
+fn block_comments_span_lines() {
+    let (tokens, errors) = tokens("1 /* one\ntwo\n*/ 2 /**/ 3");
+    assert!(errors.is_empty());
+    assert_eq!(
+        kinds_and_lines(&tokens),
+        [
+            (TokenKind::NumberLiteral, 1),
+            (TokenKind::NumberLiteral, 3),
+            (TokenKind::NumberLiteral, 3),
+            (TokenKind::Eof, 3),
+        ]
+    );
+}
+
+#[test]
+fn block_comments_nest() {
+    let (tokens, errors) = tokens("/* outer /* inner\n */ still comment */ x");
+    assert!(errors.is_empty());
+    assert_eq!(
+        kinds_and_lines(&tokens),
+        [(TokenKind::Identifier, 2), (TokenKind::Eof, 2)]
+    );
+}
+
+#[test]
+fn unterminated_block_comment_reports_its_start() {
+    let (tokens, errors) = tokens("x\n/* open /* nested */\n\n");
+    assert_eq!(
+        kinds_and_lines(&tokens),
+        [(TokenKind::Identifier, 1), (TokenKind::Eof, 4)]
+    );
+    match errors.as_slice() {
+        [err @ LexerError::UnterminatedBlockComment(span)] => {
+            assert_eq!(span.line, 2);
+            assert_eq!(
+                err.to_string(),
+                "[line 2] Error: Unterminated block comment."
+            );
+        }
+        other => panic!("unexpected errors: {other:?}"),
+    }
+}
+
+fn string_values(source: &str) -> Vec<String> {
+    let (tokens, errors) = tokens(source);
+    assert!(errors.is_empty(), "{source}: {errors:?}");
+    tokens
+        .into_iter()
+        .filter_map(|token| match token.literal {
+            Some(TokenLiteral::Str(value)) => Some(value.into_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn strings_without_escapes_borrow_the_source() {
+    let (tokens, _) = tokens(r#""plain" "tab\there""#);
+    assert!(matches!(
+        tokens[0].literal,
+        Some(TokenLiteral::Str(Cow::Borrowed("plain")))
+    ));
+    assert!(matches!(
+        &tokens[1].literal,
+        Some(TokenLiteral::Str(Cow::Owned(value))) if value == "tab\there"
+    ));
+}
+
+#[test]
+fn string_escapes_are_resolved() {
+    assert_eq!(
+        string_values(r#""a\tb\n" "\"quoted\"" "back\\slash" "\r\0" "\u{e9}\u{1F600}""#),
+        ["a\tb\n", "\"quoted\"", "back\\slash", "\r\0", "é😀"]
+    );
+}
+
+#[test]
+fn invalid_escapes_are_errors() {
+    for (source, sequence) in [
+        (r#""\q""#, r"\q"),
+        (r#""\u{}""#, r"\u{}"),
+        (r#""\u{D800}""#, r"\u{D800}"),
+        (r#""\u{1000000}""#, r"\u{1000000}"),
+        (r#""\u{12""#, r"\u{12"),
+        (r#""\u12""#, r"\u"),
+    ] {
+        let (_, errors) = tokens(source);
+        match errors.as_slice() {
+            [LexerError::InvalidEscape {
+                sequence: found, ..
+            }] => {
+                assert_eq!(found, sequence, "{source}")
+            }
+            other => panic!("{source}: unexpected errors {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn multi_line_strings_advance_the_line() {
+    let (tokens, errors) = tokens("\"one\ntwo\n\" x \"\\z\"");
+    assert_eq!(
+        kinds_and_lines(&tokens),
+        [
+            (TokenKind::StringLiteral, 1),
+            (TokenKind::Identifier, 3),
+            (TokenKind::StringLiteral, 3),
+            (TokenKind::Eof, 3),
+        ]
+    );
+    assert_eq!(
+        tokens[0].literal,
+        Some(TokenLiteral::Str("one\ntwo\n".into()))
+    );
+    match errors.as_slice() {
+        [LexerError::InvalidEscape { span, .. }] => assert_eq!((span.line, span.column), (3, 6)),
+        other => panic!("unexpected errors: {other:?}"),
+    }
+}
+
+fn number_literals(source: &str) -> Vec<(String, String)> {
+    let (tokens, errors) = tokens(source);
+    assert!(errors.is_empty(), "{source}: {errors:?}");
+    tokens
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::NumberLiteral)
+        .map(|token| (token.lexeme.to_string(), token.literal_text().into_owned()))
+        .collect()
+}
+
+#[test]
+fn number_literal_forms() {
+    for (source, literal) in [
+        ("42", "42.0"),
+        ("200.00", "200.0"),
+        ("1234.1234", "1234.1234"),
+        ("1e10", "1.0E10"),
+        ("2.5e-3", "0.0025"),
+        ("1E+2", "100.0"),
+        ("0xFF", "255.0"),
+        ("0b1010", "10.0"),
+        ("1_000_000", "1000000.0"),
+        ("0x1f", "31.0"),
+    ] {
+        assert_eq!(
+            number_literals(source),
+            [(source.to_string(), literal.to_string())]
+        );
+    }
+}
+
+#[test]
+fn a_dot_must_be_followed_by_a_digit() {
+    let (tokens, errors) = tokens("1.2.3 4.foo");
+    assert!(errors.is_empty());
+    let kinds: Vec<_> = tokens.iter().map(|token| token.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            TokenKind::NumberLiteral,
+            TokenKind::Dot,
+            TokenKind::NumberLiteral,
+            TokenKind::NumberLiteral,
+            TokenKind::Dot,
+            TokenKind::Identifier,
+            TokenKind::Eof,
+        ]
+    );
+    assert_eq!(tokens[0].lexeme, "1.2");
+}
+
+#[test]
+fn malformed_numbers_are_errors() {
+    for source in [
+        "0x", "0xFG", "0b102", "1__0", "1_", "0x_1", "1_.5", "1e", "2.5e-", "1e+", "3E",
+    ] {
+        let (tokens, errors) = tokens(source);
+        assert_eq!(tokens.len(), 1, "{source}");
+        match errors.as_slice() {
+            [LexerError::MalformedNumber { lexeme, .. }] => assert_eq!(lexeme, source),
+            other => panic!("{source}: unexpected errors {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn unicode_identifiers() {
+    let (tokens, errors) = tokens("café 変数 _x1 𝔘x ∑");
+    let identifiers: Vec<_> = tokens
+        .iter()
+        .filter(|token| token.kind == TokenKind::Identifier)
+        .map(|token| token.lexeme)
+        .collect();
+    assert_eq!(identifiers, ["café", "変数", "_x1", "𝔘x"]);
+    assert!(matches!(
+        errors.as_slice(),
+        [LexerError::UnexpectedCharacter { ch: '∑', .. }]
+    ));
+}
+
+#[test]
+fn columns_count_characters() {
+    let (tokens, _) = tokens("\"ééé\" + 変数 /* ∑ */ x\n  ü");
+    let columns: Vec<_> = tokens
+        .iter()
+        .map(|token| (token.span.line, token.span.column))
+        .collect();
+    assert_eq!(columns, [(1, 1), (1, 7), (1, 9), (1, 20), (2, 3), (2, 4)]);
+}
+
+#[tokio::test]
+async fn invalid_utf8_is_a_source_error() {
+    let path = std::env::temp_dir().join(format!("lox-invalid-{}.lox", std::process::id()));
+    std::fs::write(&path, b"print \"ok\";\nprint \"\xff\";").unwrap();
+    let result = Source::read(path.to_str().unwrap()).await;
+    std::fs::remove_file(&path).unwrap();
+    match result {
+        Err(err @ LexerError::SourceError(_)) => {
+            assert!(
+                err.to_string()
+                    .ends_with("is not valid UTF-8 (line 2, column 8)"),
+                "{err}"
+            )
+        }
+        other => panic!("expected a source error, got {other:?}"),
+    }
+}
+
+#[test]
+fn open_delimiters_counts_unclosed_braces_and_parens() {
+    for (source, open) in [
+        ("", 0),
+        ("print 1;", 0),
+        ("fun f() {", 1),
+        ("fun f() {\n  if (x) {\n", 2),
+        ("print (1 +", 1),
+        ("print f(g(", 2),
+        ("{ ( }", 1),
+        ("class A {\n  m() {}\n}", 0),
+    ] {
+        assert_eq!(open_delimiters(source), open, "{source:?}");
+    }
+}
+
+#[test]
+fn open_delimiters_ignores_strings_and_comments() {
+    for (source, open) in [
+        ("print \"{(\";", 0),
+        ("{ print \"}\";", 1),
+        ("// {\n", 0),
+        ("{ // }\n", 1),
+        ("/* { ( */ print 1;", 0),
+        ("{ /* } */", 1),
+        ("\"unterminated {", 0),
+        ("{ /* unterminated }", 1),
+    ] {
+        assert_eq!(open_delimiters(source), open, "{source:?}");
+    }
+}
+
+#[test]
+fn open_delimiters_is_negative_with_extra_closers() {
+    assert_eq!(open_delimiters("}"), -1);
+    assert_eq!(open_delimiters("print 1); }"), -2);
+    assert_eq!(open_delimiters("{ } }"), -1);
+}
@@ -0,0 +1,75 @@
+//! Hostile or broken sources are reported as errors rather than crashing
+//! the interpreter.
+
+mod common;
+
+use codecrafters_interpreter::{lexer::Lexer, parser::Parser, Interpreter, LoxError};
+use common::run;
+
+#[test]
+fn non_ascii_symbols_outside_strings_are_errors() {
+    for source in ["∑", "1.∑", "var x = €;", "print 1 ∑ 2;", "var 1é = 1;"] {
+        assert!(matches!(run(source), Err(LoxError::Syntax(_))), "{source}");
+    }
+}
+
+#[test]
+fn long_runs_of_blank_lines_and_comments() {
+    let source = "\n".repeat(200_000) + &"// comment\n".repeat(50_000) + "print 1;";
+    run(&source).unwrap();
+}
+
+#[test]
+fn deeply_nested_expressions_are_rejected() {
+    for source in [
+        format!("print {}1{};", "(".repeat(100_000), ")".repeat(100_000)),
+        format!("print {}1;", "-".repeat(100_000)),
+        format!("{}{}", "{".repeat(100_000), "}".repeat(100_000)),
+        format!("var a; a{};", " = a".repeat(100_000)),
+    ] {
+        assert!(matches!(run(&source), Err(LoxError::Syntax(_))));
+    }
+}
+
+#[test]
+fn nesting_within_the_limit_runs() {
+    let source = format!("{{ print {}1{}; }}", "(-".repeat(400), ")".repeat(400));
+    run(&source).unwrap();
+}
+
+#[test]
+fn long_flat_chains_run() {
+    for source in [
+        format!("print 1{};", " + 1".repeat(100_000)),
+        format!("print true{};", " and true".repeat(100_000)),
+        format!("print false{};", " or false".repeat(100_000)),
+        format!("fun f() {{ return f; }} f{};", "()".repeat(100_000)),
+        format!(
+            "class A {{ f() {{ return this; }} }} A(){};",
+            ".f()".repeat(100_000)
+        ),
+    ] {
+        run(&source).unwrap();
+    }
+}
+
+#[test]
+fn long_flat_chains_parse_and_print() {
+    let source = format!("1{}", " * 1".repeat(100_000));
+    let expr = Parser::new(Lexer::from_source(&source))
+        .parse_expression()
+        .unwrap();
+    assert!(format!("{expr:?}").starts_with("(* (* (* "));
+}
+
+#[test]
+fn runaway_recursion_is_a_runtime_error() {
+    let result = run("fun f() { return f(); } f();");
+    assert!(matches!(result, Err(LoxError::Runtime(_))));
+}
+
+#[test]
+fn eval_rejects_trailing_tokens() {
+    let result = Interpreter::with_output(std::io::sink()).eval("1 2");
+    assert!(matches!(result, Err(LoxError::Syntax(_))));
+}
@@ -0,0 +1,55 @@
+//! Numbers are written exactly as the reference implementation writes them.
+
+use codecrafters_interpreter::{
+    lexer::Lexer, number, parser::Parser, EvaluationValue, Interpreter,
+};
+
+const CASES: &[(f64, &str, &str)] = &[
+    (0.0, "0.0", "0"),
+    (-0.0, "-0.0", "-0"),
+    (10.0, "10.0", "10"),
+    (10.4, "10.4", "10.4"),
+    (-3.25, "-3.25", "-3.25"),
+    (0.001, "0.001", "0.001"),
+    (0.0001, "1.0E-4", "1.0E-4"),
+    (0.000123, "1.23E-4", "1.23E-4"),
+    (1234567.0, "1234567.0", "1234567"),
+    (9999999.5, "9999999.5", "9999999.5"),
+    (1e7, "1.0E7", "1.0E7"),
+    (12345678.9, "1.23456789E7", "1.23456789E7"),
+    (-1e21, "-1.0E21", "-1.0E21"),
+    (f64::MAX, "1.7976931348623157E308", "1.7976931348623157E308"),
+    (f64::NAN, "NaN", "NaN"),
+    (f64::INFINITY, "Infinity", "Infinity"),
+    (f64::NEG_INFINITY, "-Infinity", "-Infinity"),
+];
+
+#[test]
+fn literal_and_display_forms() {
+    for &(n, literal, display) in CASES {
+        assert_eq!(number::literal(n), literal, "{n:?}");
+        assert_eq!(number::display(n), display, "{n:?}");
+    }
+}
+
+#[test]
+fn print_str_and_parse_agree() {
+    let mut lox = Interpreter::with_output(std::io::sink());
+    for (source, printed) in [
+        ("10", "10"),
+        ("0 / 0", "NaN"),
+        ("1 / 0", "Infinity"),
+        ("-0", "-0"),
+        ("100000 * 100000", "1.0E10"),
+    ] {
+        match lox.eval(&format!("str({source})")).unwrap() {
+            EvaluationValue::Str(s) => assert_eq!(s, printed, "{source}"),
+            other => panic!("str({source}) gave {other:?}"),
+        }
+    }
+
+    let expr = Parser::new(Lexer::from_source("(100000000 + 0.5)"))
+        .parse_expression()
+        .unwrap();
+    assert_eq!(format!("{expr:?}"), "(group (+ 1.0E8 0.5))");
+}
@@ -0,0 +1,256 @@
+//! Every operator applied to every kind of value must either produce a value
+//! or a runtime error, never a panic.
+
+mod common;
+
+use codecrafters_interpreter::{EvaluationValue, Interpreter, LoxError};
+use common::output;
+
+const PRELUDE: &str = r#"
+fun function() {}
+class Class {}
+var instance = Class();
+var list = split("a,b", ",");
+"#;
+
+/// One expression per kind of value, with whether it is truthy.
+const VALUES: &[(&str, bool)] = &[
+    ("nil", false),
+    ("true", true),
+    ("false", false),
+    ("0", true),
+    ("1.5", true),
+    ("\"\"", true),
+    ("\"text\"", true),
+    ("function", true),
+    ("clock", true),
+    ("Class", true),
+    ("instance", true),
+    ("list", true),
+];
+
+const ARITHMETIC: &[&str] = &["-", "*", "/"];
+const COMPARISON: &[&str] = &["<", "<=", ">", ">="];
+
+fn interpreter() -> Interpreter {
+    let mut interpreter = Interpreter::with_output(std::io::sink());
+    interpreter.run(PRELUDE).expect("prelude runs");
+    interpreter
+}
+
+fn eval(interpreter: &mut Interpreter, source: &str) -> Result<EvaluationValue, LoxError> {
+    interpreter.eval(source)
+}
+
+fn is_number(source: &str) -> bool {
+    source.parse::<f64>().is_ok()
+}
+
+fn is_string(source: &str) -> bool {
+    source.starts_with('"')
+}
+
+fn assert_runtime_error(result: Result<EvaluationValue, LoxError>, source: &str) {
+    match result {
+        Err(LoxError::Runtime(_)) => {}
+        other => panic!("expected a runtime error from `{source}`, got {other:?}"),
+    }
+}
+
+#[test]
+fn negation_requires_a_number() {
+    let mut lox = interpreter();
+    for (value, _) in VALUES {
+        let source = format!("-{value}");
+        let result = eval(&mut lox, &source);
+        if is_number(value) {
+            assert!(matches!(result, Ok(EvaluationValue::Number(_))), "{source}");
+        } else {
+            assert_runtime_error(result, &source);
+        }
+    }
+}
+
+#[test]
+fn not_follows_truthiness() {
+    let mut lox = interpreter();
+    for (value, truthy) in VALUES {
+        let source = format!("!{value}");
+        match eval(&mut lox, &source) {
+            Ok(EvaluationValue::Logical(result)) => assert_eq!(result, !truthy, "{source}"),
+            other => panic!("`{source}` gave {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn arithmetic_requires_numbers() {
+    let mut lox = interpreter();
+    for op in ARITHMETIC {
+        for (left, _) in VALUES {
+            for (right, _) in VALUES {
+                let source = format!("{left} {op} {right}");
+                let result = eval(&mut lox, &source);
+                if is_number(left) && is_number(right) {
+                    assert!(matches!(result, Ok(EvaluationValue::Number(_))), "{source}");
+                } else {
+                    assert_runtime_error(result, &source);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn addition_takes_two_numbers_or_two_strings() {
+    let mut lox = interpreter();
+    for (left, _) in VALUES {
+        for (right, _) in VALUES {
+            let source = format!("{left} + {right}");
+            let result = eval(&mut lox, &source);
+            if is_number(left) && is_number(right) {
+                assert!(matches!(result, Ok(EvaluationValue::Number(_))), "{source}");
+            } else if is_string(left) && is_string(right) {
+                assert!(matches!(result, Ok(EvaluationValue::Str(_))), "{source}");
+            } else {
+                assert_runtime_error(result, &source);
+            }
+        }
+    }
+}
+
+#[test]
+fn comparison_requires_numbers() {
+    let mut lox = interpreter();
+    for op in COMPARISON {
+        for (left, _) in VALUES {
+            for (right, _) in VALUES {
+                let source = format!("{left} {op} {right}");
+                let result = eval(&mut lox, &source);
+                if is_number(left) && is_number(right) {
+                    assert!(
+                        matches!(result, Ok(EvaluationValue::Logical(_))),
+                        "{source}"
+                    );
+                } else {
+                    assert_runtime_error(result, &source);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn inequality_is_the_negation_of_equality() {
+    let mut lox = interpreter();
+    for (left, _) in VALUES {
+        for (right, _) in VALUES {
+            let equal = eval(&mut lox, &format!("{left} == {right}"));
+            let not_equal = eval(&mut lox, &format!("{left} != {right}"));
+            match (equal, not_equal) {
+                (Ok(EvaluationValue::Logical(eq)), Ok(EvaluationValue::Logical(ne))) => {
+                    assert_eq!(eq, !ne, "{left} == {right}")
+                }
+                other => panic!("comparing `{left}` and `{right}` gave {other:?}"),
+            }
+        }
+    }
+}
+
+#[test]
+fn values_equal_only_themselves() {
+    let mut lox = interpreter();
+    for (i, (left, _)) in VALUES.iter().enumerate() {
+        for (j, (right, _)) in VALUES.iter().enumerate() {
+            let source = format!("{left} == {right}");
+            assert!(
+                matches!(eval(&mut lox, &source), Ok(EvaluationValue::Logical(eq)) if eq == (i == j)),
+                "{source}"
+            );
+        }
+    }
+}
+
+#[test]
+fn equality_semantics() {
+    let mut lox = interpreter();
+    let cases = [
+        ("nil == nil", true),
+        ("nil == false", false),
+        ("0 == false", false),
+        ("\"1\" == 1", false),
+        ("\"a\" + \"b\" == \"ab\"", true),
+        ("0 == -0", true),
+        ("0 / 0 == 0 / 0", false),
+        ("0 / 0 != 0 / 0", true),
+        ("Class() == Class()", false),
+        ("split(\"a\", \",\") == split(\"a\", \",\")", false),
+    ];
+    for (source, expected) in cases {
+        assert!(
+            matches!(eval(&mut lox, source), Ok(EvaluationValue::Logical(eq)) if eq == expected),
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn logical_operators_return_an_operand() {
+    let mut lox = interpreter();
+    for (left, truthy) in VALUES {
+        for (right, _) in VALUES {
+            for (op, picks_left) in [("and", !truthy), ("or", *truthy)] {
+                let source = format!("{left} {op} {right}");
+                let expected = if picks_left { left } else { right };
+                let expected = format!("{:?}", eval(&mut lox, expected).unwrap());
+                let result = eval(&mut lox, &source).unwrap();
+                assert_eq!(format!("{result:?}"), expected, "{source}");
+            }
+        }
+    }
+}
+
+#[test]
+fn logical_operators_skip_the_right_operand() {
+    let source = r#"
+fun f(value) {
+  print "evaluated";
+  return value;
+}
+print false and undefinedVariable;
+print nil and -"not a number";
+print true or f(1);
+print "yes" or undefinedFunction();
+print false and f(1);
+print true and f(2);
+print nil or f(3);
+"#;
+    assert_eq!(
+        output(source).unwrap(),
+        "false\nnil\ntrue\nyes\nfalse\nevaluated\n2\nevaluated\n3\n"
+    );
+}
+
+#[test]
+fn operand_errors_name_what_the_operator_accepts() {
+    let mut lox = interpreter();
+    for (source, message) in [
+        ("1 + nil", "Operands must be two numbers or two strings."),
+        ("\"a\" + 1", "Operands must be two numbers or two strings."),
+        (
+            "list + list",
+            "Operands must be two numbers or two strings.",
+        ),
+        ("1 - \"a\"", "Operands must be numbers."),
+        ("\"a\" * \"b\"", "Operands must be numbers."),
+        ("nil < 1", "Operands must be numbers."),
+        ("-\"a\"", "Operand must be a number."),
+    ] {
+        match eval(&mut lox, source) {
+            Err(err @ LoxError::Runtime(_)) => {
+                assert_eq!(err.to_string(), format!("{message}\n[line 1]"), "{source}")
+            }
+            other => panic!("expected a runtime error from `{source}`, got {other:?}"),
+        }
+    }
+}
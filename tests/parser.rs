@@ -0,0 +1,91 @@
+//! Parsing a program one statement at a time.
+
+use codecrafters_interpreter::{
+    lexer::Lexer,
+    parser::{error::ParserError, Parser, Stmt},
+};
+
+fn statements(source: &str) -> Vec<Result<Stmt, Vec<ParserError>>> {
+    Parser::new(Lexer::from_source(source)).collect()
+}
+
+fn messages(errors: &[ParserError]) -> Vec<String> {
+    errors
+        .iter()
+        .map(|err| match err {
+            ParserError::LexerError(err) => err.to_string(),
+            err => err.to_string(),
+        })
+        .collect()
+}
+
+#[test]
+fn yields_each_statement_in_order() {
+    let printed: Vec<_> = statements("print 1;\nvar x = 2;\n{ x; }")
+        .into_iter()
+        .map(|stmt| format!("{:?}", stmt.unwrap()))
+        .collect();
+    assert_eq!(printed.len(), 3);
+    assert!(printed[0].starts_with("print"), "{printed:?}");
+}
+
+#[test]
+fn errors_belong_to_the_statement_they_are_in() {
+    let stmts = statements("print 1;\nprint (;\nprint 3; #");
+    assert_eq!(stmts.len(), 4);
+    assert!(stmts[0].is_ok());
+    assert_eq!(
+        messages(stmts[1].as_ref().unwrap_err()),
+        ["[line 2] Error at ';': Expect expression."]
+    );
+    assert!(stmts[2].is_ok(), "reading ahead must not blame `print 3;`");
+    assert_eq!(
+        messages(stmts[3].as_ref().unwrap_err()),
+        ["[line 3] Error: Unexpected character: #"]
+    );
+}
+
+#[test]
+fn later_input_is_not_read_early() {
+    let mut parser = Parser::new(Lexer::from_source("print 1; print \"unterminated"));
+    assert!(parser.next().unwrap().is_ok());
+    assert_eq!(
+        messages(&parser.next().unwrap().unwrap_err()),
+        [
+            "[line 1] Error: Unterminated string.",
+            "[line 1] Error at end: Expect expression."
+        ]
+    );
+    assert!(parser.next().is_none());
+}
+
+#[test]
+fn final_semicolon_can_be_optional() {
+    let parse = |source, optional| {
+        Parser::new(Lexer::from_source(source))
+            .with_optional_final_semicolon(optional)
+            .parse()
+    };
+    for source in ["1 + 2", "print 1", "var x = 1; x"] {
+        assert!(parse(source, true).is_ok(), "{source}");
+        assert!(parse(source, false).is_err(), "{source}");
+    }
+    assert!(parse("print 1 print 2", true).is_err());
+}
+
+#[test]
+fn an_expression_must_use_all_the_input() {
+    for (source, error) in [
+        ("1 2", "[line 1] Error at '2': Expect end of expression."),
+        (
+            "(1)\n+ 2 )",
+            "[line 2] Error at ')': Expect end of expression.",
+        ),
+    ] {
+        let result = Parser::new(Lexer::from_source(source)).parse_expression();
+        assert_eq!(messages(&result.unwrap_err()), [error], "{source}");
+    }
+    assert!(Parser::new(Lexer::from_source("1 + 2 "))
+        .parse_expression()
+        .is_ok());
+}
@@ -0,0 +1,158 @@
+//! Static errors found by the resolver, and variables bound to the scope
+//! they were declared in.
+
+mod common;
+
+use codecrafters_interpreter::{
+    lexer::Lexer, parser::Parser, resolver::ResolverError, EvaluationValue, Interpreter, LoxError,
+};
+use common::{output, run, SharedOutput};
+
+fn resolve_errors(source: &str) -> Vec<ResolverError> {
+    match run(source) {
+        Err(LoxError::Resolve(errors)) => errors,
+        other => panic!("expected resolver errors from `{source}`, got {other:?}"),
+    }
+}
+
+fn single_error(source: &str) -> String {
+    match resolve_errors(source).as_slice() {
+        [err] => err.to_string(),
+        other => panic!("expected one error from `{source}`, got {other:?}"),
+    }
+}
+
+#[test]
+fn each_resolver_error_is_reported() {
+    for (source, message) in [
+        (
+            "{ var a = 1; { var a = a; } }",
+            "[line 1] Error at 'a': Can't read local variable in its own initializer.",
+        ),
+        (
+            "fun f() {\n  var a;\n  var a;\n}",
+            "[line 3] Error at 'a': Already a variable with this name in this scope.",
+        ),
+        (
+            "fun f(a, a) {}",
+            "[line 1] Error at 'a': Already a variable with this name in this scope.",
+        ),
+        (
+            "return 1;",
+            "[line 1] Error at 'return': Can't return from top-level code.",
+        ),
+        (
+            "class A {\n  init() {\n    return 1;\n  }\n}",
+            "[line 3] Error at 'return': Can't return a value from an initializer.",
+        ),
+        (
+            "print this;",
+            "[line 1] Error at 'this': Can't use 'this' outside of a class.",
+        ),
+        (
+            "fun f() { return this; }",
+            "[line 1] Error at 'this': Can't use 'this' outside of a class.",
+        ),
+        (
+            "print super.x;",
+            "[line 1] Error at 'super': Can't use 'super' outside of a class.",
+        ),
+        (
+            "class A {\n  f() { super.f(); }\n}",
+            "[line 2] Error at 'super': Can't use 'super' in a class with no superclass.",
+        ),
+        (
+            "class A < A {}",
+            "[line 1] Error at 'A': A class can't inherit from itself.",
+        ),
+    ] {
+        assert_eq!(single_error(source), message, "{source}");
+    }
+}
+
+#[test]
+fn redeclaring_globals_is_allowed() {
+    assert_eq!(output("var a = 1; var a = a + 1; print a;").unwrap(), "2\n");
+}
+
+#[test]
+fn bare_return_is_allowed_in_an_initializer() {
+    let source = "class A { init() { this.x = 1; return; this.x = 2; } } print A().x;";
+    assert_eq!(output(source).unwrap(), "1\n");
+}
+
+#[test]
+fn every_error_in_a_program_is_reported() {
+    let errors = resolve_errors("return 1;\nprint this;\nclass A < A {}");
+    let lines: Vec<_> = errors.iter().map(|err| err.to_string()).collect();
+    assert_eq!(lines.len(), 3, "{lines:?}");
+    assert!(lines[0].starts_with("[line 1]"));
+    assert!(lines[2].starts_with("[line 3]"));
+}
+
+#[test]
+fn closures_keep_the_binding_they_were_resolved_to() {
+    let source = r#"
+var a = "global";
+{
+  fun showA() {
+    print a;
+  }
+
+  showA();
+  var a = "block";
+  showA();
+  print a;
+}
+"#;
+    assert_eq!(output(source).unwrap(), "global\nglobal\nblock\n");
+}
+
+#[test]
+fn resolver_errors_stop_the_program_before_it_runs() {
+    let err = run("print 1;\nreturn;").unwrap_err();
+    assert_eq!(err.exit_code(), 65);
+
+    let out = std::process::Command::new(env!("CARGO_BIN_EXE_codecrafters-interpreter"))
+        .args(["run", "-e", "print 1;\nreturn;"])
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(65));
+    assert!(out.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&out.stderr).trim_end(),
+        "[line 2] Error at 'return': Can't return from top-level code."
+    );
+}
+
+#[test]
+fn parsed_programs_are_resolved_before_running() {
+    let out = SharedOutput::default();
+    let mut interpreter = Interpreter::with_output(out.clone());
+    let stmts = Parser::new(Lexer::from_source("{ var a = 1; print a; }"))
+        .parse()
+        .unwrap();
+    interpreter.run_stmts(&stmts).unwrap();
+    assert_eq!(out.contents(), "1\n");
+
+    let stmts = Parser::new(Lexer::from_source("return;")).parse().unwrap();
+    assert!(matches!(
+        interpreter.run_stmts(&stmts),
+        Err(LoxError::Resolve(_))
+    ));
+
+    let expr = Parser::new(Lexer::from_source("this"))
+        .parse_expression()
+        .unwrap();
+    assert!(matches!(
+        interpreter.eval_expr(&expr),
+        Err(LoxError::Resolve(_))
+    ));
+    let expr = Parser::new(Lexer::from_source("clock != nil"))
+        .parse_expression()
+        .unwrap();
+    assert!(matches!(
+        interpreter.eval_expr(&expr),
+        Ok(EvaluationValue::Logical(true))
+    ));
+}
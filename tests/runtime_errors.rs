@@ -0,0 +1,64 @@
+//! Where runtime errors are reported, and the trace of statements and
+//! calls they unwind through.
+
+mod common;
+
+use codecrafters_interpreter::{Interpreter, LoxError};
+use common::runtime_error;
+
+fn error_line(source: &str) -> String {
+    let message = runtime_error(source);
+    message.lines().last().unwrap_or_default().to_string()
+}
+
+#[test]
+fn operator_errors_report_the_operator_line() {
+    for (source, line) in [
+        ("print \"a\"\n  + 1;", "[line 2]"),
+        ("print 1\n\n  < nil;", "[line 3]"),
+        ("var x = nil;\nprint 1 +\n  2 *\n  x;", "[line 3]"),
+        ("print -\n  \"a\";", "[line 1]"),
+        ("print\n  -nil;", "[line 2]"),
+    ] {
+        assert_eq!(error_line(source), line, "{source}");
+    }
+}
+
+#[test]
+fn trace_lists_frames_from_innermost_outwards() {
+    let source = "\
+fun inner(x) {
+  return x + nil;
+}
+fun outer() {
+  return inner(1);
+}
+print outer();";
+    let mut interpreter = Interpreter::with_output(std::io::sink());
+    let err = interpreter.run(source).unwrap_err();
+    assert!(matches!(err, LoxError::Runtime(_)));
+    let frames: Vec<_> = interpreter
+        .trace()
+        .iter()
+        .map(|frame| (frame.description.as_str(), frame.span.line))
+        .collect();
+    assert_eq!(
+        frames,
+        [
+            ("return statement", 2),
+            ("call to 'inner'", 5),
+            ("return statement", 5),
+            ("call to 'outer'", 7),
+            ("print statement", 7),
+        ]
+    );
+}
+
+#[test]
+fn trace_is_cleared_by_the_next_run() {
+    let mut interpreter = Interpreter::with_output(std::io::sink());
+    interpreter.run("print nil + 1;").unwrap_err();
+    assert!(!interpreter.trace().is_empty());
+    interpreter.run("print 1;").unwrap();
+    assert!(interpreter.trace().is_empty());
+}
@@ -0,0 +1,146 @@
+//! Parsing a program from a reader as it is read.
+
+mod common;
+
+use std::io::{BufRead, BufReader, Read};
+
+use codecrafters_interpreter::{
+    lexer::Lexer,
+    parser::{error::ParserError, Parser, Stmt, StreamParser},
+    Interpreter,
+};
+use common::SharedOutput;
+
+/// Each statement's tree and span, or its error messages.
+fn describe(stmts: impl Iterator<Item = Result<Stmt, Vec<ParserError>>>) -> Vec<String> {
+    stmts
+        .map(|stmt| match stmt {
+            Ok(stmt) => format!("{:?} {:?}", stmt, stmt.span),
+            Err(errors) => errors
+                .iter()
+                .map(|err| match err {
+                    ParserError::LexerError(err) => err.to_string(),
+                    err => err.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        })
+        .collect()
+}
+
+fn streamed(source: &str) -> Vec<String> {
+    describe(StreamParser::new(source.as_bytes()))
+}
+
+fn whole(source: &str) -> Vec<String> {
+    describe(Parser::new(Lexer::from_source(source)))
+}
+
+/// A reader that repeats `line` forever, counting the bytes handed out.
+struct Endless {
+    line: &'static [u8],
+    position: usize,
+    read: usize,
+}
+
+impl Read for Endless {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut written = 0;
+        while written < buf.len() {
+            buf[written] = self.line[self.position];
+            self.position = (self.position + 1) % self.line.len();
+            written += 1;
+        }
+        self.read += written;
+        Ok(written)
+    }
+}
+
+#[test]
+fn matches_parsing_the_whole_source() {
+    let source = r#"
+var a = 1; print a;
+if (a > 0) {
+  print "big";
+}
+else
+  print "small";
+if (a) print 1;
+
+// a comment; with a semicolon
+else print 2;
+for (var i = 0; i < 2; i = i + 1) print i;
+/* a block comment
+   spanning lines; with { and ( in it */
+print "a string
+spanning lines; with } in it";
+fun f(x) {
+  return x;
+}
+class A < B {
+  m() { return super.m(); }
+}
+print f(
+  1
+);
+print 3"#;
+    assert_eq!(streamed(source), whole(source));
+}
+
+#[test]
+fn errors_report_lines_of_the_whole_input() {
+    let source = "print 1;\n\nprint (;\nvar x = @;\n{\n  print 2;\n\n\nprint 3;";
+    let stmts = streamed(source);
+    assert_eq!(stmts, whole(source));
+    assert!(stmts.iter().any(|stmt| stmt.starts_with("[line 3]")));
+    assert!(stmts.iter().any(|stmt| stmt.starts_with("[line 4]")));
+    assert_eq!(
+        stmts.last().unwrap(),
+        "[line 9] Error at end: Expect '}' after block."
+    );
+}
+
+#[test]
+fn statements_are_yielded_before_the_input_ends() {
+    let mut endless = Endless {
+        line: b"print 1;\n",
+        position: 0,
+        read: 0,
+    };
+    let stmts = StreamParser::new(BufReader::with_capacity(64, &mut endless))
+        .take(10_000)
+        .filter(Result::is_ok)
+        .count();
+    assert_eq!(stmts, 10_000);
+    assert!(
+        endless.read < 10_001 * 9 + 64,
+        "read {} bytes",
+        endless.read
+    );
+}
+
+#[test]
+fn read_errors_end_the_stream() {
+    let reader = BufReader::new(&b"print 1;\nprint \xff;\nprint 3;\n"[..]);
+    let stmts: Vec<_> = StreamParser::new(reader).collect();
+    assert_eq!(stmts.len(), 2);
+    assert!(stmts[0].is_ok());
+    assert!(matches!(
+        stmts[1].as_ref().unwrap_err().as_slice(),
+        [ParserError::LexerError(_)]
+    ));
+}
+
+#[test]
+fn streamed_statements_run_one_at_a_time() {
+    let out = SharedOutput::default();
+    let mut interpreter = Interpreter::with_output(out.clone());
+    let reader: &mut dyn BufRead =
+        &mut "var n = 2;\nfun twice(x) {\n  return x * n;\n}\nprint twice(21);\n".as_bytes();
+    for stmt in StreamParser::new(reader) {
+        interpreter
+            .run_stmts(std::slice::from_ref(&stmt.unwrap()))
+            .unwrap();
+    }
+    assert_eq!(out.contents(), "42\n");
+}